@@ -0,0 +1,365 @@
+//! # Checksumx
+//!
+//! This module provides streaming checksums over files, readers and in-memory data. Input is
+//! processed in fixed-size chunks so arbitrarily large artifacts can be hashed without loading
+//! them into memory.
+//!
+//! ## Overview
+//!
+//! The main components are:
+//! - `Algorithm`: The supported algorithms (SHA-256, SHA-512, CRC32)
+//! - `Hasher`: An incremental hasher that also implements `std::io::Write`
+//! - `Checksum`: A computed digest with hex and base64 encodings
+//! - `hash_file`, `hash_reader`, `hash_bytes`: One-shot helpers
+//! - `verify`: Compares a file against an expected checksum
+//!
+//! ## Usage Scenarios
+//!
+//! Ideal for:
+//! - Verifying downloaded or deployed artifacts against published digests
+//! - Detecting changes to files without comparing their contents
+//!
+//! ### Example
+//! ```rust,no_run
+//! use x::checksumx::{self, Algorithm, Checksum};
+//!
+//! # fn run() -> Result<(), x::errorsx::Errorsx> {
+//! let digest = checksumx::hash_file("release.tar.gz", Algorithm::Sha256)?;
+//! println!("{}", digest.to_hex());
+//!
+//! let expected = Checksum::from_hex(Algorithm::Sha256, "9f86d081884c7d65...")?;
+//! checksumx::verify("release.tar.gz", &expected)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    fmt::{Display, Write as _},
+    fs::File,
+    io::{self, Read, Write},
+    path::Path,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::errorsx::Errorsx;
+
+/// Size of the buffer used when streaming from a reader
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Supported checksum algorithms
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    Sha256,
+    Sha512,
+    Crc32,
+}
+
+impl Algorithm {
+    /// Gets the length of this algorithm's digest
+    ///
+    /// # Returns
+    /// The digest length in bytes
+    pub fn digest_len(&self) -> usize {
+        match self {
+            Algorithm::Sha256 => 32,
+            Algorithm::Sha512 => 64,
+            Algorithm::Crc32 => 4,
+        }
+    }
+
+    /// Gets the conventional lowercase name of the algorithm
+    ///
+    /// # Returns
+    /// The algorithm name, e.g. `sha256`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Algorithm::Sha256 => "sha256",
+            Algorithm::Sha512 => "sha512",
+            Algorithm::Crc32 => "crc32",
+        }
+    }
+}
+
+impl Display for Algorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A computed digest together with the algorithm that produced it
+///
+/// CRC32 digests are stored big-endian, matching their usual hex representation.
+///
+/// # Fields
+/// * `algorithm` - The algorithm that produced the digest
+/// * `bytes` - The raw digest
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Checksum {
+    algorithm: Algorithm,
+    bytes: Vec<u8>,
+}
+
+/// Display implementation for Checksum
+///
+/// Formats the digest as `<algorithm>:<hex>`
+impl Display for Checksum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.algorithm, self.to_hex())
+    }
+}
+
+impl Checksum {
+    /// Parses a hex-encoded digest
+    ///
+    /// # Parameters
+    /// * `algorithm` - The algorithm the digest was produced with
+    /// * `hex` - The digest as hex, case-insensitive
+    ///
+    /// # Returns
+    /// The parsed Checksum, or an `Errorsx` if the input is not valid hex of the right length
+    #[track_caller]
+    pub fn from_hex(algorithm: Algorithm, hex: &str) -> Result<Self, Errorsx> {
+        let hex = hex.trim();
+        let invalid = || {
+            Errorsx::builder("Invalid hex checksum")
+                .with_context(format!("Algorithm: {}", algorithm))
+                .with_context(format!("Value: {}", hex))
+                .build()
+        };
+        if hex.len() != algorithm.digest_len() * 2 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| invalid())?;
+        Ok(Self { algorithm, bytes })
+    }
+
+    /// Parses a base64-encoded digest
+    ///
+    /// # Parameters
+    /// * `algorithm` - The algorithm the digest was produced with
+    /// * `encoded` - The digest as standard, padded base64
+    ///
+    /// # Returns
+    /// The parsed Checksum, or an `Errorsx` if the input is not valid base64 of the right length
+    #[track_caller]
+    pub fn from_base64(algorithm: Algorithm, encoded: &str) -> Result<Self, Errorsx> {
+        let encoded = encoded.trim();
+        let invalid = || {
+            Errorsx::builder("Invalid base64 checksum")
+                .with_context(format!("Algorithm: {}", algorithm))
+                .with_context(format!("Value: {}", encoded))
+        };
+        let bytes = STANDARD
+            .decode(encoded)
+            .map_err(|err| invalid().with_source(err).build())?;
+        if bytes.len() != algorithm.digest_len() {
+            return Err(invalid().build());
+        }
+        Ok(Self { algorithm, bytes })
+    }
+
+    /// Gets the algorithm that produced the digest
+    ///
+    /// # Returns
+    /// The Algorithm
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Gets the raw digest
+    ///
+    /// # Returns
+    /// The digest bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Encodes the digest as lowercase hex
+    ///
+    /// # Returns
+    /// The hex string
+    pub fn to_hex(&self) -> String {
+        let mut hex = String::with_capacity(self.bytes.len() * 2);
+        for byte in &self.bytes {
+            let _ = write!(hex, "{:02x}", byte);
+        }
+        hex
+    }
+
+    /// Encodes the digest as standard, padded base64
+    ///
+    /// # Returns
+    /// The base64 string
+    pub fn to_base64(&self) -> String {
+        STANDARD.encode(&self.bytes)
+    }
+}
+
+/// Algorithm-specific hashing state
+enum State {
+    Sha256(Sha256),
+    Sha512(Sha512),
+    Crc32(crc32fast::Hasher),
+}
+
+/// An incremental hasher
+///
+/// Data can be fed with `update` or by writing to the hasher, e.g. with `std::io::copy`.
+pub struct Hasher {
+    state: State,
+}
+
+impl Hasher {
+    /// Creates a new Hasher for the given algorithm
+    ///
+    /// # Parameters
+    /// * `algorithm` - The algorithm to use
+    ///
+    /// # Returns
+    /// A Hasher with no data consumed
+    pub fn new(algorithm: Algorithm) -> Self {
+        let state = match algorithm {
+            Algorithm::Sha256 => State::Sha256(Sha256::new()),
+            Algorithm::Sha512 => State::Sha512(Sha512::new()),
+            Algorithm::Crc32 => State::Crc32(crc32fast::Hasher::new()),
+        };
+        Self { state }
+    }
+
+    /// Feeds data into the hasher
+    ///
+    /// # Parameters
+    /// * `data` - The next chunk of input
+    pub fn update(&mut self, data: &[u8]) {
+        match &mut self.state {
+            State::Sha256(hasher) => hasher.update(data),
+            State::Sha512(hasher) => hasher.update(data),
+            State::Crc32(hasher) => hasher.update(data),
+        }
+    }
+
+    /// Consumes the hasher and returns the digest
+    ///
+    /// # Returns
+    /// The Checksum of all data fed so far
+    pub fn finalize(self) -> Checksum {
+        match self.state {
+            State::Sha256(hasher) => Checksum {
+                algorithm: Algorithm::Sha256,
+                bytes: hasher.finalize().to_vec(),
+            },
+            State::Sha512(hasher) => Checksum {
+                algorithm: Algorithm::Sha512,
+                bytes: hasher.finalize().to_vec(),
+            },
+            State::Crc32(hasher) => Checksum {
+                algorithm: Algorithm::Crc32,
+                bytes: hasher.finalize().to_be_bytes().to_vec(),
+            },
+        }
+    }
+}
+
+impl Write for Hasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Computes the checksum of in-memory data
+///
+/// # Parameters
+/// * `data` - The data to hash
+/// * `algorithm` - The algorithm to use
+///
+/// # Returns
+/// The Checksum of the data
+pub fn hash_bytes(data: &[u8], algorithm: Algorithm) -> Checksum {
+    let mut hasher = Hasher::new(algorithm);
+    hasher.update(data);
+    hasher.finalize()
+}
+
+/// Computes the checksum of everything readable from a reader
+///
+/// # Parameters
+/// * `reader` - The reader to consume
+/// * `algorithm` - The algorithm to use
+///
+/// # Returns
+/// The Checksum of the read data, or an `Errorsx` if reading failed
+#[track_caller]
+pub fn hash_reader(mut reader: impl Read, algorithm: Algorithm) -> Result<Checksum, Errorsx> {
+    let mut hasher = Hasher::new(algorithm);
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => hasher.update(&buffer[..n]),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => {
+                return Err(Errorsx::builder("Failed to read data for checksum")
+                    .with_context(format!("Algorithm: {}", algorithm))
+                    .with_source(err)
+                    .build())
+            }
+        }
+    }
+    Ok(hasher.finalize())
+}
+
+/// Computes the checksum of a file
+///
+/// # Parameters
+/// * `path` - The file to hash
+/// * `algorithm` - The algorithm to use
+///
+/// # Returns
+/// The Checksum of the file contents, or an `Errorsx` if the file could not be read
+#[track_caller]
+pub fn hash_file(path: impl AsRef<Path>, algorithm: Algorithm) -> Result<Checksum, Errorsx> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|err| {
+        Errorsx::builder("Failed to open file for checksum")
+            .with_context(format!("Path: {}", path.display()))
+            .with_source(err)
+            .build()
+    })?;
+    hash_reader(file, algorithm)
+}
+
+/// Verifies that a file matches an expected checksum
+///
+/// The file is hashed with the algorithm of `expected`.
+///
+/// # Parameters
+/// * `path` - The file to verify
+/// * `expected` - The checksum the file must match
+///
+/// # Returns
+/// `Ok(())` if the digests match, otherwise an `Errorsx` carrying the path and both digests in
+/// its context
+#[track_caller]
+pub fn verify(path: impl AsRef<Path>, expected: &Checksum) -> Result<(), Errorsx> {
+    let path = path.as_ref();
+    let actual = hash_file(path, expected.algorithm)?;
+    if actual == *expected {
+        return Ok(());
+    }
+    Err(Errorsx::builder("Checksum mismatch")
+        .with_context(format!("Path: {}", path.display()))
+        .with_context(format!("Expected: {}", expected))
+        .with_context(format!("Actual: {}", actual))
+        .build())
+}
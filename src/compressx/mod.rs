@@ -0,0 +1,428 @@
+//! # Compressx
+//!
+//! This module provides streaming gzip and zstd compression for log shipping and API payloads.
+//! Data is moved between any `Read` and `Write` in fixed-size chunks, so large inputs never have
+//! to be held in memory. Every decompression takes a size limit, which stops highly compressed
+//! "decompression bombs" before they exhaust memory or disk.
+//!
+//! ## Overview
+//!
+//! The main components are:
+//! - `Format`: The supported formats, with detection from magic bytes and `Content-Encoding`
+//! - `compress`, `decompress`: Streaming helpers for any format
+//! - `compress_gzip`, `decompress_gzip`, `compress_zstd`, `decompress_zstd`: Format-specific
+//!   streaming helpers
+//! - `gzip_bytes`, `gunzip_bytes`, `zstd_bytes`, `unzstd_bytes`: In-memory helpers
+//!
+//! ## Usage Scenarios
+//!
+//! Ideal for:
+//! - Compressing rotated log files before shipping them
+//! - Decoding `Content-Encoding: gzip` or `zstd` request bodies with a bounded output size
+//!
+//! ### Example
+//! ```rust
+//! use x::compressx::{self, DEFAULT_MAX_DECOMPRESSED_SIZE};
+//!
+//! # fn run() -> Result<(), x::errorsx::Errorsx> {
+//! let payload = br#"{"event":"login","user":42}"#;
+//! let compressed = compressx::gzip_bytes(payload)?;
+//!
+//! let restored = compressx::gunzip_bytes(&compressed, DEFAULT_MAX_DECOMPRESSED_SIZE)?;
+//! assert_eq!(restored, payload);
+//!
+//! // Output beyond the limit is rejected instead of being buffered
+//! assert!(compressx::gunzip_bytes(&compressed, 8).is_err());
+//! # Ok(())
+//! # }
+//! # run().unwrap();
+//! ```
+
+use std::{
+    fmt::Display,
+    io::{self, Read, Write},
+};
+
+use flate2::{read::MultiGzDecoder, write::GzEncoder, Compression};
+
+use crate::errorsx::Errorsx;
+
+/// Size of the buffer used when streaming between a reader and a writer
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// A conservative decompressed size limit for request bodies and similar payloads (64 MiB)
+pub const DEFAULT_MAX_DECOMPRESSED_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Smallest zstd window accepted, the window zstd declares for streams of unknown size at its
+/// default level
+const ZSTD_MIN_WINDOW_LOG: u32 = 21;
+
+/// Largest zstd window accepted, matching zstd's own default limit
+const ZSTD_MAX_WINDOW_LOG: u32 = 27;
+
+/// Magic bytes starting every gzip member
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Magic bytes starting every zstd frame
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Supported compression formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    Gzip,
+    Zstd,
+}
+
+impl Format {
+    /// Detects the format of compressed data from its leading magic bytes
+    ///
+    /// # Parameters
+    /// * `data` - The start of the compressed data
+    ///
+    /// # Returns
+    /// The detected Format, or `None` if the data starts with neither gzip nor zstd magic
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        if data.starts_with(&GZIP_MAGIC) {
+            Some(Format::Gzip)
+        } else if data.starts_with(&ZSTD_MAGIC) {
+            Some(Format::Zstd)
+        } else {
+            None
+        }
+    }
+
+    /// Maps an HTTP `Content-Encoding` value to a format
+    ///
+    /// # Parameters
+    /// * `value` - The header value, e.g. `gzip`
+    ///
+    /// # Returns
+    /// The matching Format, or `None` for other encodings such as `identity` or `br`
+    pub fn from_content_encoding(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "gzip" | "x-gzip" => Some(Format::Gzip),
+            "zstd" => Some(Format::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Gets the HTTP `Content-Encoding` token for the format
+    ///
+    /// # Returns
+    /// The token, e.g. `gzip`
+    pub fn content_encoding(&self) -> &'static str {
+        match self {
+            Format::Gzip => "gzip",
+            Format::Zstd => "zstd",
+        }
+    }
+
+    /// Gets the conventional file extension for the format
+    ///
+    /// # Returns
+    /// The extension without a leading dot, e.g. `gz`
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Gzip => "gz",
+            Format::Zstd => "zst",
+        }
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.content_encoding())
+    }
+}
+
+/// Compresses everything readable from a reader into a writer
+///
+/// The default compression level of the format is used. The compressed stream is finished
+/// before returning, and the writer is flushed.
+///
+/// # Parameters
+/// * `reader` - The uncompressed input
+/// * `writer` - Receives the compressed output
+/// * `format` - The format to produce
+///
+/// # Returns
+/// The number of uncompressed bytes consumed, or an `Errorsx` if reading or writing failed
+#[track_caller]
+pub fn compress(reader: impl Read, writer: impl Write, format: Format) -> Result<u64, Errorsx> {
+    let write_error = |err: io::Error| {
+        Errorsx::builder("Failed to write compressed data")
+            .with_context(format!("Format: {}", format))
+            .with_source(err)
+            .build()
+    };
+
+    match format {
+        Format::Gzip => {
+            let mut encoder = GzEncoder::new(writer, Compression::default());
+            let consumed = pump(reader, &mut encoder, format)?;
+            let mut writer = encoder.finish().map_err(write_error)?;
+            writer.flush().map_err(write_error)?;
+            Ok(consumed)
+        }
+        Format::Zstd => {
+            let mut encoder = zstd::stream::write::Encoder::new(writer, 0).map_err(write_error)?;
+            let consumed = pump(reader, &mut encoder, format)?;
+            let mut writer = encoder.finish().map_err(write_error)?;
+            writer.flush().map_err(write_error)?;
+            Ok(consumed)
+        }
+    }
+}
+
+/// Decompresses everything readable from a reader into a writer
+///
+/// Concatenated gzip members and zstd frames are decoded as one stream. Decoding stops with an
+/// error as soon as the output would exceed `max_size`; output written up to that point is left
+/// in the writer. zstd frames declaring a window larger than `max_size` (but at least 2 MiB and
+/// at most 128 MiB) are rejected, so the limit also bounds the decoder's memory.
+///
+/// # Parameters
+/// * `reader` - The compressed input
+/// * `writer` - Receives the decompressed output
+/// * `format` - The format of the input
+/// * `max_size` - The maximum number of decompressed bytes to accept
+///
+/// # Returns
+/// The number of decompressed bytes written, or an `Errorsx` if the input is corrupt, the limit
+/// is exceeded (status code 413) or reading or writing failed
+#[track_caller]
+pub fn decompress(
+    reader: impl Read,
+    writer: impl Write,
+    format: Format,
+    max_size: u64,
+) -> Result<u64, Errorsx> {
+    match format {
+        Format::Gzip => copy_limited(MultiGzDecoder::new(reader), writer, format, max_size),
+        Format::Zstd => {
+            let mut decoder = zstd::stream::read::Decoder::new(reader).map_err(|err| {
+                Errorsx::builder("Failed to decompress data")
+                    .with_context(format!("Format: {}", format))
+                    .with_source(err)
+                    .build()
+            })?;
+            // Frames declare the window the decoder must allocate up front, so bound it by the
+            // output limit rather than zstd's default of 128 MiB
+            decoder
+                .window_log_max(zstd_window_log(max_size))
+                .map_err(|err| {
+                    Errorsx::builder("Failed to decompress data")
+                        .with_context(format!("Format: {}", format))
+                        .with_source(err)
+                        .build()
+                })?;
+            copy_limited(decoder, writer, format, max_size)
+        }
+    }
+}
+
+/// Compresses a stream with gzip
+///
+/// # Parameters
+/// * `reader` - The uncompressed input
+/// * `writer` - Receives the gzip output
+///
+/// # Returns
+/// The number of uncompressed bytes consumed, or an `Errorsx` if reading or writing failed
+#[track_caller]
+pub fn compress_gzip(reader: impl Read, writer: impl Write) -> Result<u64, Errorsx> {
+    compress(reader, writer, Format::Gzip)
+}
+
+/// Decompresses a gzip stream
+///
+/// # Parameters
+/// * `reader` - The gzip input
+/// * `writer` - Receives the decompressed output
+/// * `max_size` - The maximum number of decompressed bytes to accept
+///
+/// # Returns
+/// The number of decompressed bytes written, or an `Errorsx` as described for `decompress`
+#[track_caller]
+pub fn decompress_gzip(
+    reader: impl Read,
+    writer: impl Write,
+    max_size: u64,
+) -> Result<u64, Errorsx> {
+    decompress(reader, writer, Format::Gzip, max_size)
+}
+
+/// Compresses a stream with zstd
+///
+/// # Parameters
+/// * `reader` - The uncompressed input
+/// * `writer` - Receives the zstd output
+///
+/// # Returns
+/// The number of uncompressed bytes consumed, or an `Errorsx` if reading or writing failed
+#[track_caller]
+pub fn compress_zstd(reader: impl Read, writer: impl Write) -> Result<u64, Errorsx> {
+    compress(reader, writer, Format::Zstd)
+}
+
+/// Decompresses a zstd stream
+///
+/// # Parameters
+/// * `reader` - The zstd input
+/// * `writer` - Receives the decompressed output
+/// * `max_size` - The maximum number of decompressed bytes to accept
+///
+/// # Returns
+/// The number of decompressed bytes written, or an `Errorsx` as described for `decompress`
+#[track_caller]
+pub fn decompress_zstd(
+    reader: impl Read,
+    writer: impl Write,
+    max_size: u64,
+) -> Result<u64, Errorsx> {
+    decompress(reader, writer, Format::Zstd, max_size)
+}
+
+/// Compresses in-memory data with gzip
+///
+/// # Parameters
+/// * `data` - The data to compress
+///
+/// # Returns
+/// The gzip-compressed data, or an `Errorsx` if compression failed
+#[track_caller]
+pub fn gzip_bytes(data: &[u8]) -> Result<Vec<u8>, Errorsx> {
+    let mut output = Vec::new();
+    compress(data, &mut output, Format::Gzip)?;
+    Ok(output)
+}
+
+/// Decompresses in-memory gzip data
+///
+/// # Parameters
+/// * `data` - The gzip-compressed data
+/// * `max_size` - The maximum number of decompressed bytes to accept
+///
+/// # Returns
+/// The decompressed data, or an `Errorsx` as described for `decompress`
+#[track_caller]
+pub fn gunzip_bytes(data: &[u8], max_size: u64) -> Result<Vec<u8>, Errorsx> {
+    let mut output = Vec::new();
+    decompress(data, &mut output, Format::Gzip, max_size)?;
+    Ok(output)
+}
+
+/// Compresses in-memory data with zstd
+///
+/// # Parameters
+/// * `data` - The data to compress
+///
+/// # Returns
+/// The zstd-compressed data, or an `Errorsx` if compression failed
+#[track_caller]
+pub fn zstd_bytes(data: &[u8]) -> Result<Vec<u8>, Errorsx> {
+    let mut output = Vec::new();
+    compress(data, &mut output, Format::Zstd)?;
+    Ok(output)
+}
+
+/// Decompresses in-memory zstd data
+///
+/// # Parameters
+/// * `data` - The zstd-compressed data
+/// * `max_size` - The maximum number of decompressed bytes to accept
+///
+/// # Returns
+/// The decompressed data, or an `Errorsx` as described for `decompress`
+#[track_caller]
+pub fn unzstd_bytes(data: &[u8], max_size: u64) -> Result<Vec<u8>, Errorsx> {
+    let mut output = Vec::new();
+    decompress(data, &mut output, Format::Zstd, max_size)?;
+    Ok(output)
+}
+
+/// Streams uncompressed input into an encoder
+#[track_caller]
+fn pump(mut reader: impl Read, mut encoder: impl Write, format: Format) -> Result<u64, Errorsx> {
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    let mut consumed = 0u64;
+    loop {
+        let n = match reader.read(&mut buffer) {
+            Ok(0) => return Ok(consumed),
+            Ok(n) => n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => {
+                return Err(Errorsx::builder("Failed to read data for compression")
+                    .with_context(format!("Format: {}", format))
+                    .with_source(err)
+                    .build())
+            }
+        };
+        encoder.write_all(&buffer[..n]).map_err(|err| {
+            Errorsx::builder("Failed to write compressed data")
+                .with_context(format!("Format: {}", format))
+                .with_source(err)
+                .build()
+        })?;
+        consumed += n as u64;
+    }
+}
+
+/// Streams decoder output into a writer, enforcing the size limit
+#[track_caller]
+fn copy_limited(
+    mut decoder: impl Read,
+    mut writer: impl Write,
+    format: Format,
+    max_size: u64,
+) -> Result<u64, Errorsx> {
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    let mut written = 0u64;
+    loop {
+        // Never ask for more than one byte past the limit, so a bomb is detected without
+        // decoding further than necessary.
+        let remaining = max_size.saturating_sub(written).saturating_add(1);
+        let want = buffer
+            .len()
+            .min(usize::try_from(remaining).unwrap_or(usize::MAX));
+        let n = match decoder.read(&mut buffer[..want]) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => {
+                return Err(Errorsx::builder("Failed to decompress data")
+                    .with_context(format!("Format: {}", format))
+                    .with_source(err)
+                    .build())
+            }
+        };
+        if written + n as u64 > max_size {
+            return Err(Errorsx::builder("Decompressed data exceeds size limit")
+                .with_context(format!("Format: {}", format))
+                .with_context(format!("Limit: {} bytes", max_size))
+                .with_status_code(413)
+                .with_status("Payload Too Large")
+                .build());
+        }
+        writer.write_all(&buffer[..n]).map_err(|err| {
+            Errorsx::builder("Failed to write decompressed data")
+                .with_context(format!("Format: {}", format))
+                .with_source(err)
+                .build()
+        })?;
+        written += n as u64;
+    }
+    writer.flush().map_err(|err| {
+        Errorsx::builder("Failed to write decompressed data")
+            .with_context(format!("Format: {}", format))
+            .with_source(err)
+            .build()
+    })?;
+    Ok(written)
+}
+
+/// Computes the largest zstd window worth accepting for a decompressed size limit
+fn zstd_window_log(max_size: u64) -> u32 {
+    let needed = u64::BITS - max_size.saturating_sub(1).leading_zeros();
+    needed.clamp(ZSTD_MIN_WINDOW_LOG, ZSTD_MAX_WINDOW_LOG)
+}
@@ -0,0 +1,129 @@
+//! # Concurrencyx
+//!
+//! This module provides bounded parallel mapping over collections. An async closure is applied
+//! to every item with at most `limit` invocations running at once, and the outputs are returned
+//! in the same order as the inputs regardless of completion order.
+//!
+//! ## Overview
+//!
+//! The main components are:
+//! - `try_map_concurrent`: Maps a collection concurrently, cancelling outstanding work on the
+//!   first error
+//! - `try_map_concurrent_with`: Same as above with a configurable `ErrorMode`
+//! - `ErrorMode`: Whether to stop at the first error or run every item and collect all failures
+//!
+//! Failures are reported as an `ErrorsxGroup`, ordered by the position of the failing item.
+//! The closures run on the calling task; cancelling means their futures are dropped.
+//!
+//! ### Example
+//! ```rust,no_run
+//! use x::concurrencyx;
+//! use x::errorsx::Errorsx;
+//!
+//! # async fn fetch(id: u32) -> Result<String, Errorsx> { Ok(id.to_string()) }
+//! # async fn run() -> Result<(), x::errorsx::ErrorsxGroup> {
+//! let ids = vec![1, 2, 3, 4];
+//! let names = concurrencyx::try_map_concurrent(ids, 2, fetch).await?;
+//! assert_eq!(names.len(), 4);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+
+use futures::{stream, StreamExt};
+
+use crate::errorsx::{Errorsx, ErrorsxGroup};
+
+/// How `try_map_concurrent_with` reacts to a failing item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorMode {
+    /// Cancel outstanding work and return as soon as one item fails
+    #[default]
+    FailFast,
+    /// Run every item to completion and return all failures together
+    CollectAll,
+}
+
+/// Maps a collection concurrently, cancelling outstanding work on the first error
+///
+/// # Parameters
+/// * `items` - The items to map
+/// * `limit` - Maximum number of closures running at once, clamped to at least 1
+/// * `f` - Async closure applied to every item
+///
+/// # Returns
+/// The outputs in input order, or an `ErrorsxGroup` holding the first error
+pub async fn try_map_concurrent<I, T, U, F, Fut>(
+    items: I,
+    limit: usize,
+    f: F,
+) -> Result<Vec<U>, ErrorsxGroup>
+where
+    I: IntoIterator<Item = T>,
+    F: FnMut(T) -> Fut,
+    Fut: Future<Output = Result<U, Errorsx>>,
+{
+    try_map_concurrent_with(items, limit, ErrorMode::FailFast, f).await
+}
+
+/// Maps a collection concurrently with a configurable reaction to errors
+///
+/// # Parameters
+/// * `items` - The items to map
+/// * `limit` - Maximum number of closures running at once, clamped to at least 1
+/// * `mode` - Whether to stop at the first error or collect all of them
+/// * `f` - Async closure applied to every item
+///
+/// # Returns
+/// The outputs in input order, or an `ErrorsxGroup` with the failures ordered by item position
+pub async fn try_map_concurrent_with<I, T, U, F, Fut>(
+    items: I,
+    limit: usize,
+    mode: ErrorMode,
+    mut f: F,
+) -> Result<Vec<U>, ErrorsxGroup>
+where
+    I: IntoIterator<Item = T>,
+    F: FnMut(T) -> Fut,
+    Fut: Future<Output = Result<U, Errorsx>>,
+{
+    let items = items.into_iter();
+    let mut outputs: Vec<Option<U>> = Vec::with_capacity(items.size_hint().0);
+    let mut failures: Vec<(usize, Errorsx)> = Vec::new();
+
+    let mut running = stream::iter(items.enumerate())
+        .map(|(index, item)| {
+            let fut = f(item);
+            async move { (index, fut.await) }
+        })
+        .buffer_unordered(limit.max(1));
+
+    while let Some((index, result)) = running.next().await {
+        match result {
+            Ok(output) => {
+                if outputs.len() <= index {
+                    outputs.resize_with(index + 1, || None);
+                }
+                outputs[index] = Some(output);
+            }
+            Err(err) => {
+                failures.push((index, err));
+                if mode == ErrorMode::FailFast {
+                    break;
+                }
+            }
+        }
+    }
+
+    if !failures.is_empty() {
+        failures.sort_by_key(|(index, _)| *index);
+        return Err(failures
+            .into_iter()
+            .map(|(_, err)| err)
+            .collect::<Vec<_>>()
+            .into());
+    }
+
+    Ok(outputs.into_iter().flatten().collect())
+}
@@ -0,0 +1,318 @@
+//! Cookie attributes and header formatting
+//!
+//! This module provides the `Cookie` type with its builder, which formats `Set-Cookie` header
+//! values, and `parse_cookie_header` for reading the name/value pairs of a request's `Cookie`
+//! header.
+
+use std::{fmt::Display, time::Duration};
+
+use crate::errorsx::Errorsx;
+
+/// The `SameSite` cookie attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    None,
+}
+
+impl Display for SameSite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SameSite::Strict => f.write_str("Strict"),
+            SameSite::Lax => f.write_str("Lax"),
+            SameSite::None => f.write_str("None"),
+        }
+    }
+}
+
+/// A cookie with its attributes, ready to be sent in a `Set-Cookie` header
+///
+/// # Fields
+/// * `name` - The cookie name
+/// * `value` - The already encoded cookie value
+/// * `path` - Optional `Path` attribute
+/// * `domain` - Optional `Domain` attribute
+/// * `max_age` - Optional `Max-Age` attribute
+/// * `same_site` - Optional `SameSite` attribute
+/// * `secure` - Whether the `Secure` attribute is set
+/// * `http_only` - Whether the `HttpOnly` attribute is set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cookie {
+    name: String,
+    value: String,
+    path: Option<String>,
+    domain: Option<String>,
+    max_age: Option<Duration>,
+    same_site: Option<SameSite>,
+    secure: bool,
+    http_only: bool,
+}
+
+/// Display implementation for Cookie
+///
+/// Formats the cookie as a `Set-Cookie` header value
+impl Display for Cookie {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}", self.name, self.value)?;
+        if let Some(path) = &self.path {
+            write!(f, "; Path={}", path)?;
+        }
+        if let Some(domain) = &self.domain {
+            write!(f, "; Domain={}", domain)?;
+        }
+        if let Some(max_age) = self.max_age {
+            write!(f, "; Max-Age={}", max_age.as_secs())?;
+        }
+        if let Some(same_site) = self.same_site {
+            write!(f, "; SameSite={}", same_site)?;
+        }
+        if self.secure {
+            f.write_str("; Secure")?;
+        }
+        if self.http_only {
+            f.write_str("; HttpOnly")?;
+        }
+        Ok(())
+    }
+}
+
+impl Cookie {
+    /// Creates a new CookieBuilder with the given name and value
+    ///
+    /// # Parameters
+    /// * `name` - The cookie name
+    /// * `value` - The already encoded cookie value
+    ///
+    /// # Returns
+    /// A CookieBuilder instance for fluent construction
+    pub fn builder(name: impl Into<String>, value: impl Into<String>) -> CookieBuilder {
+        CookieBuilder::new(name, value)
+    }
+
+    /// Gets the cookie name
+    ///
+    /// # Returns
+    /// A string slice containing the name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Gets the encoded cookie value
+    ///
+    /// # Returns
+    /// A string slice containing the value
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Formats the cookie as a `Set-Cookie` header value
+    ///
+    /// # Returns
+    /// The header value
+    pub fn to_header_value(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Builder for constructing a Cookie with a fluent interface
+///
+/// Starts with `Path=/`, `SameSite=Lax`, `Secure` and `HttpOnly`, the safe choice for cookies
+/// not meant to be read by scripts.
+#[derive(Debug, Clone)]
+pub struct CookieBuilder {
+    cookie: Cookie,
+}
+
+impl CookieBuilder {
+    /// Creates a new CookieBuilder with the given name and value
+    ///
+    /// # Parameters
+    /// * `name` - The cookie name
+    /// * `value` - The already encoded cookie value
+    ///
+    /// # Returns
+    /// A new CookieBuilder with secure defaults
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            cookie: Cookie {
+                name: name.into(),
+                value: value.into(),
+                path: Some("/".to_string()),
+                domain: None,
+                max_age: None,
+                same_site: Some(SameSite::Lax),
+                secure: true,
+                http_only: true,
+            },
+        }
+    }
+
+    /// Sets the `Path` attribute
+    ///
+    /// # Parameters
+    /// * `path` - The path the cookie applies to
+    ///
+    /// # Returns
+    /// Self with the path set for chaining
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.cookie.path = Some(path.into());
+        self
+    }
+
+    /// Sets the `Domain` attribute
+    ///
+    /// # Parameters
+    /// * `domain` - The domain the cookie applies to
+    ///
+    /// # Returns
+    /// Self with the domain set for chaining
+    pub fn with_domain(mut self, domain: impl Into<String>) -> Self {
+        self.cookie.domain = Some(domain.into());
+        self
+    }
+
+    /// Sets the `Max-Age` attribute
+    ///
+    /// # Parameters
+    /// * `max_age` - How long the cookie lives, truncated to whole seconds
+    ///
+    /// # Returns
+    /// Self with the max age set for chaining
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.cookie.max_age = Some(max_age);
+        self
+    }
+
+    /// Sets the `SameSite` attribute
+    ///
+    /// `SameSite::None` also sets `Secure`, as browsers reject it otherwise.
+    ///
+    /// # Parameters
+    /// * `same_site` - The SameSite policy
+    ///
+    /// # Returns
+    /// Self with the SameSite policy set for chaining
+    pub fn with_same_site(mut self, same_site: SameSite) -> Self {
+        self.cookie.same_site = Some(same_site);
+        if same_site == SameSite::None {
+            self.cookie.secure = true;
+        }
+        self
+    }
+
+    /// Sets whether the `Secure` attribute is present
+    ///
+    /// # Parameters
+    /// * `secure` - Whether the cookie is only sent over HTTPS
+    ///
+    /// # Returns
+    /// Self with the flag set for chaining
+    pub fn with_secure(mut self, secure: bool) -> Self {
+        self.cookie.secure = secure || self.cookie.same_site == Some(SameSite::None);
+        self
+    }
+
+    /// Sets whether the `HttpOnly` attribute is present
+    ///
+    /// # Parameters
+    /// * `http_only` - Whether the cookie is hidden from scripts
+    ///
+    /// # Returns
+    /// Self with the flag set for chaining
+    pub fn with_http_only(mut self, http_only: bool) -> Self {
+        self.cookie.http_only = http_only;
+        self
+    }
+
+    /// Builds and returns the final Cookie instance
+    ///
+    /// The name must be an RFC 6265 token and the value may only contain cookie octets, so
+    /// neither can end the pair early. `Path` and `Domain` may not contain `;` or control
+    /// characters, which would let them inject further attributes or headers.
+    ///
+    /// # Returns
+    /// A Cookie with all the configured attributes, or an `Errorsx` if the name, value or an
+    /// attribute is invalid
+    #[track_caller]
+    pub fn build(self) -> Result<Cookie, Errorsx> {
+        let cookie = self.cookie;
+        validate_name(&cookie.name)?;
+        if !cookie.value.bytes().all(is_cookie_octet) {
+            return Err(Errorsx::builder("Invalid cookie value")
+                .with_context(format!("Cookie: {}", cookie.name))
+                .with_context(format!("Value: {:?}", cookie.value))
+                .build());
+        }
+        validate_attribute(&cookie.name, "Path", cookie.path.as_deref())?;
+        validate_attribute(&cookie.name, "Domain", cookie.domain.as_deref())?;
+        Ok(cookie)
+    }
+}
+
+/// Checks that a cookie name is a non-empty RFC 6265 token
+///
+/// # Parameters
+/// * `name` - The cookie name
+///
+/// # Returns
+/// `Ok(())` if the name is valid, otherwise an `Errorsx`
+#[track_caller]
+pub(crate) fn validate_name(name: &str) -> Result<(), Errorsx> {
+    if !name.is_empty() && name.bytes().all(is_token_char) {
+        return Ok(());
+    }
+    Err(Errorsx::builder("Invalid cookie name")
+        .with_context(format!("Cookie: {:?}", name))
+        .build())
+}
+
+/// Checks that an attribute value cannot terminate the attribute or the header
+#[track_caller]
+fn validate_attribute(name: &str, attribute: &str, value: Option<&str>) -> Result<(), Errorsx> {
+    match value {
+        Some(value) if value.contains(';') || value.chars().any(char::is_control) => {
+            Err(Errorsx::builder("Invalid cookie attribute")
+                .with_context(format!("Cookie: {}", name))
+                .with_context(format!("{}: {:?}", attribute, value))
+                .build())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Checks whether a byte is an RFC 7230 `tchar`
+fn is_token_char(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&byte)
+}
+
+/// Checks whether a byte is an RFC 6265 `cookie-octet`
+fn is_cookie_octet(byte: u8) -> bool {
+    matches!(byte, 0x21 | 0x23..=0x2B | 0x2D..=0x3A | 0x3C..=0x5B | 0x5D..=0x7E)
+}
+
+/// Parses the name/value pairs of a request `Cookie` header
+///
+/// # Parameters
+/// * `header` - The header value, e.g. `a=1; b=2`
+///
+/// # Returns
+/// The pairs in header order; malformed entries without `=` are skipped
+pub fn parse_cookie_header(header: &str) -> Vec<(&str, &str)> {
+    header
+        .split(';')
+        .filter_map(|pair| {
+            let (name, value) = pair.split_once('=')?;
+            let name = name.trim();
+            if name.is_empty() {
+                return None;
+            }
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            Some((name, value))
+        })
+        .collect()
+}
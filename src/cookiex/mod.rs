@@ -0,0 +1,472 @@
+//! # Cookiex
+//!
+//! This module provides tamper-proof cookie values so that small pieces of state, such as session
+//! hints and flash messages, can be stored client-side. A value is serialized to JSON and then
+//! either signed with HMAC-SHA256 or encrypted with AES-256-GCM. The cookie name is bound into
+//! the signature (or the associated data) so a value cannot be moved to another cookie.
+//!
+//! ## Overview
+//!
+//! The main components are:
+//! - `Key`: Signing and encryption keys derived from a secret
+//! - `CookieCodec`: Encodes and decodes values, with fallback keys for rotation
+//! - `Protection`: Whether values are only signed or also encrypted
+//! - `Cookie` / `CookieBuilder`: Cookie attributes and `Set-Cookie` formatting
+//! - `parse_cookie_header`: Reads name/value pairs from a request `Cookie` header
+//!
+//! ## Key Rotation
+//!
+//! Values are always produced with the primary key. Keys added with `with_fallback_key` are only
+//! used for decoding, so a new primary key can be rolled out while cookies issued with the
+//! previous one stay valid until they expire.
+//!
+//! ### Example
+//! ```rust
+//! use std::time::Duration;
+//! use x::cookiex::{CookieCodec, Key};
+//!
+//! # fn run() -> Result<(), x::errorsx::Errorsx> {
+//! let previous = Key::from_secret(b"previous secret, at least 32 bytes long")?;
+//! let current = Key::from_secret(b"current secret, also at least 32 bytes")?;
+//! let codec = CookieCodec::encrypted(current).with_fallback_key(previous);
+//!
+//! let cookie = codec
+//!     .cookie("flash", &"Profile updated")?
+//!     .with_max_age(Duration::from_secs(60))
+//!     .build()?;
+//! println!("Set-Cookie: {}", cookie);
+//!
+//! // The browser sends the value back in the request's Cookie header
+//! let header = format!("theme=dark; {}={}", cookie.name(), cookie.value());
+//! let flash: Option<String> = codec.decode_from_header(&header, "flash")?;
+//! assert_eq!(flash.as_deref(), Some("Profile updated"));
+//! # Ok(())
+//! # }
+//! # run().unwrap();
+//! ```
+pub mod cookie;
+
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+    Aes256Gcm, Nonce,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use serde::{de::DeserializeOwned, Serialize};
+use sha2::Sha256;
+
+use crate::errorsx::Errorsx;
+
+pub use cookie::{parse_cookie_header, Cookie, CookieBuilder, SameSite};
+
+/// Minimum length of the secret keys are derived from
+pub const MIN_SECRET_LEN: usize = 32;
+
+/// Largest `name=value` pair browsers are guaranteed to store
+const MAX_COOKIE_LEN: usize = 4096;
+
+/// Length of an AES-GCM nonce
+const NONCE_LEN: usize = 12;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Signing and encryption keys used by a CookieCodec
+///
+/// Both keys are derived from a single secret, so one secret can be used for either protection
+/// mode without reusing key material across algorithms.
+#[derive(Clone)]
+pub struct Key {
+    signing: [u8; 32],
+    encryption: [u8; 32],
+}
+
+/// Debug implementation for Key
+///
+/// Never prints key material
+impl std::fmt::Debug for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Key(..)")
+    }
+}
+
+impl Key {
+    /// Derives a key from a secret
+    ///
+    /// # Parameters
+    /// * `secret` - At least `MIN_SECRET_LEN` bytes of high-entropy secret material
+    ///
+    /// # Returns
+    /// The derived Key, or an `Errorsx` if the secret is too short
+    #[track_caller]
+    pub fn from_secret(secret: &[u8]) -> Result<Self, Errorsx> {
+        if secret.len() < MIN_SECRET_LEN {
+            return Err(Errorsx::builder("Cookie secret is too short")
+                .with_context(format!(
+                    "Expected at least {} bytes, got {}",
+                    MIN_SECRET_LEN,
+                    secret.len()
+                ))
+                .build());
+        }
+        Ok(Self {
+            signing: derive(secret, b"x.cookiex.signing"),
+            encryption: derive(secret, b"x.cookiex.encryption"),
+        })
+    }
+
+    /// Generates a random key
+    ///
+    /// Useful for tests and single-process deployments; keys that must survive restarts or be
+    /// shared between instances should be derived with `from_secret`.
+    ///
+    /// # Returns
+    /// A new random Key
+    pub fn generate() -> Self {
+        let secret = Aes256Gcm::generate_key(&mut OsRng);
+        Self {
+            signing: derive(&secret, b"x.cookiex.signing"),
+            encryption: derive(&secret, b"x.cookiex.encryption"),
+        }
+    }
+}
+
+/// Derives a purpose-specific 32 byte key from a secret
+fn derive(secret: &[u8], label: &[u8]) -> [u8; 32] {
+    let mut mac =
+        <HmacSha256 as Mac>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(label);
+    mac.finalize().into_bytes().into()
+}
+
+/// How cookie values are protected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protection {
+    /// Values are readable by the client but cannot be modified
+    Signed,
+    /// Values can neither be read nor modified by the client
+    Encrypted,
+}
+
+/// Encodes values into protected cookie values and decodes them back
+///
+/// # Fields
+/// * `keys` - The primary key followed by fallback keys accepted when decoding
+/// * `protection` - Whether values are signed or encrypted
+#[derive(Debug, Clone)]
+pub struct CookieCodec {
+    keys: Vec<Key>,
+    protection: Protection,
+}
+
+impl CookieCodec {
+    /// Creates a codec that signs values
+    ///
+    /// # Parameters
+    /// * `key` - The primary key
+    ///
+    /// # Returns
+    /// A new CookieCodec using `Protection::Signed`
+    pub fn signed(key: Key) -> Self {
+        Self::new(key, Protection::Signed)
+    }
+
+    /// Creates a codec that encrypts values
+    ///
+    /// # Parameters
+    /// * `key` - The primary key
+    ///
+    /// # Returns
+    /// A new CookieCodec using `Protection::Encrypted`
+    pub fn encrypted(key: Key) -> Self {
+        Self::new(key, Protection::Encrypted)
+    }
+
+    /// Creates a codec with the given protection
+    ///
+    /// # Parameters
+    /// * `key` - The primary key
+    /// * `protection` - Whether values are signed or encrypted
+    ///
+    /// # Returns
+    /// A new CookieCodec
+    pub fn new(key: Key, protection: Protection) -> Self {
+        Self {
+            keys: vec![key],
+            protection,
+        }
+    }
+
+    /// Adds a key accepted when decoding but never used for encoding
+    ///
+    /// # Parameters
+    /// * `key` - A previously used primary key
+    ///
+    /// # Returns
+    /// Self with the fallback key added for chaining
+    pub fn with_fallback_key(mut self, key: Key) -> Self {
+        self.keys.push(key);
+        self
+    }
+
+    /// Gets the protection mode of this codec
+    ///
+    /// # Returns
+    /// The Protection
+    pub fn protection(&self) -> Protection {
+        self.protection
+    }
+
+    /// Serializes and protects a value for the named cookie
+    ///
+    /// # Parameters
+    /// * `name` - The cookie name the value is bound to
+    /// * `value` - The value to store
+    ///
+    /// # Returns
+    /// The encoded cookie value, or an `Errorsx` if the name is not a valid cookie name,
+    /// serialization or encryption failed or the cookie would exceed browser size limits
+    #[track_caller]
+    pub fn encode<T: Serialize + ?Sized>(&self, name: &str, value: &T) -> Result<String, Errorsx> {
+        cookie::validate_name(name)?;
+        let json = serde_json::to_vec(value).map_err(|err| {
+            Errorsx::builder("Failed to serialize cookie value")
+                .with_context(format!("Cookie: {}", name))
+                .with_source(err)
+                .build()
+        })?;
+
+        let key = &self.keys[0];
+        let encoded = match self.protection {
+            Protection::Signed => {
+                let payload = URL_SAFE_NO_PAD.encode(&json);
+                let tag = sign(key, name, &payload).finalize().into_bytes();
+                format!("{}.{}", payload, URL_SAFE_NO_PAD.encode(tag))
+            }
+            Protection::Encrypted => {
+                let cipher = Aes256Gcm::new(&key.encryption.into());
+                let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+                let sealed = cipher
+                    .encrypt(
+                        &nonce,
+                        Payload {
+                            msg: &json,
+                            aad: name.as_bytes(),
+                        },
+                    )
+                    .map_err(|_| {
+                        Errorsx::builder("Failed to encrypt cookie value")
+                            .with_context(format!("Cookie: {}", name))
+                            .build()
+                    })?;
+                let mut bytes = nonce.to_vec();
+                bytes.extend_from_slice(&sealed);
+                URL_SAFE_NO_PAD.encode(bytes)
+            }
+        };
+
+        if name.len() + 1 + encoded.len() > MAX_COOKIE_LEN {
+            return Err(Errorsx::builder("Cookie value is too large")
+                .with_context(format!("Cookie: {}", name))
+                .with_context(format!(
+                    "Encoded size {} exceeds {} bytes",
+                    name.len() + 1 + encoded.len(),
+                    MAX_COOKIE_LEN
+                ))
+                .build());
+        }
+        Ok(encoded)
+    }
+
+    /// Verifies, decrypts if needed and deserializes a cookie value
+    ///
+    /// The primary key is tried first, then each fallback key in the order they were added.
+    ///
+    /// # Parameters
+    /// * `name` - The cookie name the value was bound to
+    /// * `value` - The encoded cookie value
+    ///
+    /// # Returns
+    /// The decoded value, or an `Errorsx` if the value was tampered with, was produced with an
+    /// unknown key or does not deserialize into `T`
+    #[track_caller]
+    pub fn decode<T: DeserializeOwned>(&self, name: &str, value: &str) -> Result<T, Errorsx> {
+        let json = match self.protection {
+            Protection::Signed => self.verify(name, value),
+            Protection::Encrypted => self.decrypt(name, value),
+        }
+        .ok_or_else(|| {
+            Errorsx::builder("Invalid cookie value")
+                .with_context(format!("Cookie: {}", name))
+                .with_context("Value was tampered with or protected with an unknown key")
+                .build()
+        })?;
+
+        serde_json::from_slice(&json).map_err(|err| {
+            Errorsx::builder("Failed to deserialize cookie value")
+                .with_context(format!("Cookie: {}", name))
+                .with_source(err)
+                .build()
+        })
+    }
+
+    /// Encodes a value and wraps it in a CookieBuilder with secure defaults
+    ///
+    /// # Parameters
+    /// * `name` - The cookie name
+    /// * `value` - The value to store
+    ///
+    /// # Returns
+    /// A CookieBuilder for setting further attributes, or an `Errorsx` if encoding failed
+    #[track_caller]
+    pub fn cookie<T: Serialize + ?Sized>(
+        &self,
+        name: &str,
+        value: &T,
+    ) -> Result<CookieBuilder, Errorsx> {
+        let encoded = self.encode(name, value)?;
+        Ok(Cookie::builder(name, encoded))
+    }
+
+    /// Finds and decodes the named cookie in a request `Cookie` header
+    ///
+    /// # Parameters
+    /// * `header` - The `Cookie` header value
+    /// * `name` - The cookie to look for
+    ///
+    /// # Returns
+    /// `None` if the cookie is absent, the decoded value if present, or an `Errorsx` if the
+    /// cookie is present but invalid
+    #[track_caller]
+    pub fn decode_from_header<T: DeserializeOwned>(
+        &self,
+        header: &str,
+        name: &str,
+    ) -> Result<Option<T>, Errorsx> {
+        match parse_cookie_header(header)
+            .into_iter()
+            .find(|(candidate, _)| *candidate == name)
+        {
+            Some((_, value)) => self.decode(name, value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Checks the signature of a signed value against every key
+    fn verify(&self, name: &str, value: &str) -> Option<Vec<u8>> {
+        let (payload, tag) = value.rsplit_once('.')?;
+        let tag = URL_SAFE_NO_PAD.decode(tag).ok()?;
+        self.keys
+            .iter()
+            .any(|key| sign(key, name, payload).verify_slice(&tag).is_ok())
+            .then(|| URL_SAFE_NO_PAD.decode(payload).ok())
+            .flatten()
+    }
+
+    /// Decrypts an encrypted value with every key until one succeeds
+    fn decrypt(&self, name: &str, value: &str) -> Option<Vec<u8>> {
+        let bytes = URL_SAFE_NO_PAD.decode(value).ok()?;
+        if bytes.len() < NONCE_LEN {
+            return None;
+        }
+        let (nonce, sealed) = bytes.split_at(NONCE_LEN);
+        let nonce = Nonce::from_slice(nonce);
+        self.keys.iter().find_map(|key| {
+            Aes256Gcm::new(&key.encryption.into())
+                .decrypt(
+                    nonce,
+                    Payload {
+                        msg: sealed,
+                        aad: name.as_bytes(),
+                    },
+                )
+                .ok()
+        })
+    }
+}
+
+/// Starts an HMAC over the cookie name and encoded payload
+fn sign(key: &Key, name: &str, payload: &str) -> HmacSha256 {
+    let mut mac =
+        <HmacSha256 as Mac>::new_from_slice(&key.signing).expect("HMAC accepts keys of any length");
+    mac.update(name.as_bytes());
+    mac.update(b"=");
+    mac.update(payload.as_bytes());
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(seed: u8) -> Key {
+        Key::from_secret(&[seed; MIN_SECRET_LEN]).unwrap()
+    }
+
+    /// Flips one bit of the decoded bytes of a base64url segment
+    fn tamper(segment: &str) -> String {
+        let mut bytes = URL_SAFE_NO_PAD.decode(segment).unwrap();
+        bytes[0] ^= 1;
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    #[test]
+    fn signed_value_round_trips() {
+        let codec = CookieCodec::signed(key(1));
+        let encoded = codec.encode("session", &vec![1, 2, 3]).unwrap();
+        let decoded: Vec<u32> = codec.decode("session", &encoded).unwrap();
+        assert_eq!(decoded, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn flipped_byte_is_rejected() {
+        let signed = CookieCodec::signed(key(1));
+        let encoded = signed.encode("session", "admin=false").unwrap();
+        let (payload, tag) = encoded.rsplit_once('.').unwrap();
+        let tampered_payload = format!("{}.{}", tamper(payload), tag);
+        let tampered_tag = format!("{}.{}", payload, tamper(tag));
+        assert!(signed
+            .decode::<String>("session", &tampered_payload)
+            .is_err());
+        assert!(signed.decode::<String>("session", &tampered_tag).is_err());
+
+        let encrypted = CookieCodec::encrypted(key(1));
+        let encoded = encrypted.encode("session", "admin=false").unwrap();
+        assert!(encrypted
+            .decode::<String>("session", &tamper(&encoded))
+            .is_err());
+    }
+
+    #[test]
+    fn value_is_bound_to_cookie_name() {
+        let codec = CookieCodec::signed(key(1));
+        let encoded = codec.encode("session", "value").unwrap();
+        assert!(codec.decode::<String>("other", &encoded).is_err());
+    }
+
+    #[test]
+    fn fallback_key_opens_values_after_rotation() {
+        for protection in [Protection::Signed, Protection::Encrypted] {
+            let old = CookieCodec::new(key(1), protection);
+            let encoded = old.encode("session", "value").unwrap();
+
+            let rotated = CookieCodec::new(key(2), protection).with_fallback_key(key(1));
+            assert_eq!(
+                rotated.decode::<String>("session", &encoded).unwrap(),
+                "value"
+            );
+
+            let without_fallback = CookieCodec::new(key(2), protection);
+            assert!(without_fallback
+                .decode::<String>("session", &encoded)
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn encrypted_value_does_not_open_as_signed() {
+        let encrypted = CookieCodec::encrypted(key(1));
+        let encoded = encrypted.encode("session", "value").unwrap();
+        let signed = CookieCodec::signed(key(1));
+        assert!(signed.decode::<String>("session", &encoded).is_err());
+    }
+}
@@ -0,0 +1,124 @@
+//! Tower middleware applying a CorsPolicy
+//!
+//! This module provides `CorsLayer`, available with the `tower` feature. Preflight requests are
+//! answered directly: `204 No Content` with the policy headers when allowed, `403 Forbidden`
+//! without CORS headers when denied. Other requests are passed to the inner service and the
+//! policy headers are added to its response when the request is allowed. `Vary` is added to
+//! every response whose headers depend on the request origin, whatever the decision.
+
+use std::{
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use futures::future::BoxFuture;
+use hyper::{
+    header::{
+        HeaderName, HeaderValue, ACCESS_CONTROL_REQUEST_HEADERS, ACCESS_CONTROL_REQUEST_METHOD,
+        ORIGIN,
+    },
+    Method, Request, Response, StatusCode,
+};
+use tower::{Layer, Service};
+
+use super::{CorsDecision, CorsPolicy};
+
+/// Layer wrapping services with a CorsPolicy
+#[derive(Debug, Clone)]
+pub struct CorsLayer {
+    policy: Arc<CorsPolicy>,
+}
+
+impl CorsLayer {
+    /// Creates a new CorsLayer
+    ///
+    /// # Parameters
+    /// * `policy` - The policy to apply
+    ///
+    /// # Returns
+    /// A new CorsLayer
+    pub fn new(policy: CorsPolicy) -> Self {
+        Self {
+            policy: Arc::new(policy),
+        }
+    }
+}
+
+impl<S> Layer<S> for CorsLayer {
+    type Service = CorsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CorsService {
+            inner,
+            policy: Arc::clone(&self.policy),
+        }
+    }
+}
+
+/// Service produced by CorsLayer
+#[derive(Debug, Clone)]
+pub struct CorsService<S> {
+    inner: S,
+    policy: Arc<CorsPolicy>,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for CorsService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+    ResBody: Default + Send + 'static,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<ReqBody>) -> Self::Future {
+        let headers = request.headers();
+        let origin = headers.get(ORIGIN).and_then(|v| v.to_str().ok());
+
+        if request.method() == Method::OPTIONS {
+            if let Some(requested_method) = headers
+                .get(ACCESS_CONTROL_REQUEST_METHOD)
+                .and_then(|v| v.to_str().ok())
+            {
+                let requested_headers = headers
+                    .get(ACCESS_CONTROL_REQUEST_HEADERS)
+                    .and_then(|v| v.to_str().ok());
+                let decision =
+                    self.policy
+                        .evaluate_preflight(origin, requested_method, requested_headers);
+                let status = if decision.is_allowed() {
+                    StatusCode::NO_CONTENT
+                } else {
+                    StatusCode::FORBIDDEN
+                };
+                let mut response = Response::new(ResBody::default());
+                *response.status_mut() = status;
+                apply(&decision, &mut response);
+                return Box::pin(async move { Ok(response) });
+            }
+        }
+
+        let decision = self.policy.evaluate(origin, request.method().as_str());
+        let future = self.inner.call(request);
+        Box::pin(async move {
+            let mut response = future.await?;
+            apply(&decision, &mut response);
+            Ok(response)
+        })
+    }
+}
+
+/// Adds the decision's headers to a response, skipping values that are not valid header values
+fn apply<B>(decision: &CorsDecision, response: &mut Response<B>) {
+    let headers = response.headers_mut();
+    for (name, value) in decision.headers() {
+        if let Ok(value) = HeaderValue::from_str(value) {
+            headers.append(HeaderName::from_static(name), value);
+        }
+    }
+}
@@ -0,0 +1,528 @@
+//! # Corsx
+//!
+//! This module provides a CORS policy builder and a framework-agnostic evaluator. Given the
+//! `Origin` of a request and its method, the policy decides whether the request is allowed and
+//! produces the exact response headers to send, so the same policy can be applied from any HTTP
+//! stack. With the `tower` feature enabled, `layer::CorsLayer` applies a policy as middleware.
+//!
+//! ## Overview
+//!
+//! The main components are:
+//! - `CorsPolicy`: The evaluated policy, with `evaluate` and `evaluate_preflight`
+//! - `CorsPolicyBuilder`: A builder pattern implementation for constructing policies
+//! - `CorsDecision`: The outcome of an evaluation, carrying the response headers
+//! - `CorsDenial`: Why a request was denied
+//!
+//! ## Origin Patterns
+//!
+//! Allowed origins are either exact (`https://app.example.com`) or match any subdomain
+//! (`https://*.example.com`, which matches `https://a.example.com` and `https://a.b.example.com`
+//! but not `https://example.com`). Scheme and port must always match exactly.
+//!
+//! ### Example
+//! ```rust
+//! use std::time::Duration;
+//! use x::corsx::{CorsDecision, CorsPolicy};
+//!
+//! let policy = CorsPolicy::builder()
+//!     .with_origin("https://*.example.com")
+//!     .with_methods(["GET", "POST", "DELETE"])
+//!     .with_headers(["Content-Type", "Authorization"])
+//!     .with_max_age(Duration::from_secs(600))
+//!     .with_credentials(true)
+//!     .build()?;
+//!
+//! match policy.evaluate(Some("https://app.example.com"), "GET") {
+//!     CorsDecision::Allowed(headers) => assert!(!headers.is_empty()),
+//!     _ => unreachable!(),
+//! }
+//! # Ok::<(), x::errorsx::Errorsx>(())
+//! ```
+#[cfg(feature = "tower")]
+pub mod layer;
+
+use std::{fmt::Display, time::Duration};
+
+use crate::errorsx::Errorsx;
+
+/// Response header names produced by the evaluator
+pub mod header {
+    pub const ALLOW_ORIGIN: &str = "access-control-allow-origin";
+    pub const ALLOW_CREDENTIALS: &str = "access-control-allow-credentials";
+    pub const ALLOW_METHODS: &str = "access-control-allow-methods";
+    pub const ALLOW_HEADERS: &str = "access-control-allow-headers";
+    pub const EXPOSE_HEADERS: &str = "access-control-expose-headers";
+    pub const MAX_AGE: &str = "access-control-max-age";
+    pub const VARY: &str = "vary";
+}
+
+/// Methods allowed when none are configured
+const DEFAULT_METHODS: [&str; 3] = ["GET", "HEAD", "POST"];
+
+/// A single allowed origin
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum OriginPattern {
+    /// Matches one origin exactly
+    Exact(String),
+    /// Matches any subdomain of `suffix` (which starts with a dot) with the given scheme
+    Subdomain { scheme: String, suffix: String },
+}
+
+impl OriginPattern {
+    fn parse(origin: &str) -> Self {
+        let origin = origin.trim().trim_end_matches('/').to_ascii_lowercase();
+        if let Some((scheme, rest)) = origin.split_once("://") {
+            if let Some(suffix) = rest.strip_prefix('*') {
+                if suffix.starts_with('.') {
+                    return OriginPattern::Subdomain {
+                        scheme: format!("{}://", scheme),
+                        suffix: suffix.to_string(),
+                    };
+                }
+            }
+        }
+        OriginPattern::Exact(origin)
+    }
+
+    fn matches(&self, origin: &str) -> bool {
+        match self {
+            OriginPattern::Exact(exact) => exact == origin,
+            OriginPattern::Subdomain { scheme, suffix } => origin
+                .strip_prefix(scheme.as_str())
+                .and_then(|rest| rest.strip_suffix(suffix.as_str()))
+                .is_some_and(|label| {
+                    !label.is_empty()
+                        && !label.starts_with('.')
+                        && !label.ends_with('.')
+                        && label
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+                }),
+        }
+    }
+}
+
+/// Which origins a policy allows
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum AllowedOrigins {
+    Any,
+    List(Vec<OriginPattern>),
+}
+
+/// Which request headers a policy allows
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum AllowedHeaders {
+    Any,
+    List(Vec<String>),
+}
+
+/// Why a CORS request was denied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CorsDenial {
+    /// The request origin is not allowed
+    OriginNotAllowed(String),
+    /// The (requested) method is not allowed
+    MethodNotAllowed(String),
+    /// A requested header is not allowed
+    HeaderNotAllowed(String),
+}
+
+impl Display for CorsDenial {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CorsDenial::OriginNotAllowed(origin) => write!(f, "origin not allowed: {}", origin),
+            CorsDenial::MethodNotAllowed(method) => write!(f, "method not allowed: {}", method),
+            CorsDenial::HeaderNotAllowed(header) => write!(f, "header not allowed: {}", header),
+        }
+    }
+}
+
+/// The outcome of evaluating a request against a CorsPolicy
+///
+/// Every variant carries the headers to add to the response. For `NotCors` and `Denied` this is
+/// at most a `Vary` header, which keeps shared caches from serving a response computed for one
+/// origin to another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CorsDecision {
+    /// The request carries no `Origin` header and is not subject to CORS
+    NotCors(Vec<(&'static str, String)>),
+    /// The request is allowed; the listed headers must be added to the response
+    Allowed(Vec<(&'static str, String)>),
+    /// The request is denied; no CORS headers other than `Vary` should be sent
+    Denied(CorsDenial, Vec<(&'static str, String)>),
+}
+
+impl CorsDecision {
+    /// Checks whether the decision permits the request
+    ///
+    /// # Returns
+    /// True for `NotCors` and `Allowed`
+    pub fn is_allowed(&self) -> bool {
+        !matches!(self, CorsDecision::Denied(..))
+    }
+
+    /// Gets the headers to add to the response
+    ///
+    /// # Returns
+    /// The header name/value pairs; only `Vary` unless the decision is `Allowed`
+    pub fn headers(&self) -> &[(&'static str, String)] {
+        match self {
+            CorsDecision::NotCors(headers)
+            | CorsDecision::Allowed(headers)
+            | CorsDecision::Denied(_, headers) => headers,
+        }
+    }
+}
+
+/// Builder for constructing a CorsPolicy with a fluent interface
+///
+/// Starts with no allowed origins, the methods `GET`, `HEAD` and `POST`, no extra request
+/// headers, no exposed headers, no max age and credentials disabled.
+#[derive(Debug, Clone)]
+pub struct CorsPolicyBuilder {
+    policy: CorsPolicy,
+}
+
+impl Default for CorsPolicyBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CorsPolicyBuilder {
+    /// Creates a new CorsPolicyBuilder with restrictive defaults
+    ///
+    /// # Returns
+    /// A new CorsPolicyBuilder
+    pub fn new() -> Self {
+        Self {
+            policy: CorsPolicy {
+                origins: AllowedOrigins::List(Vec::new()),
+                methods: Vec::new(),
+                headers: AllowedHeaders::List(Vec::new()),
+                expose_headers: Vec::new(),
+                max_age: None,
+                credentials: false,
+            },
+        }
+    }
+
+    /// Allows an origin or a wildcard subdomain pattern
+    ///
+    /// # Parameters
+    /// * `origin` - An exact origin like `https://app.example.com` or a pattern like
+    ///   `https://*.example.com`
+    ///
+    /// # Returns
+    /// Self with the origin added for chaining
+    pub fn with_origin(mut self, origin: impl AsRef<str>) -> Self {
+        let pattern = OriginPattern::parse(origin.as_ref());
+        match &mut self.policy.origins {
+            AllowedOrigins::List(list) => list.push(pattern),
+            AllowedOrigins::Any => {}
+        }
+        self
+    }
+
+    /// Allows several origins or wildcard subdomain patterns
+    ///
+    /// # Parameters
+    /// * `origins` - The origins to add
+    ///
+    /// # Returns
+    /// Self with the origins added for chaining
+    pub fn with_origins<I, O>(self, origins: I) -> Self
+    where
+        I: IntoIterator<Item = O>,
+        O: AsRef<str>,
+    {
+        origins
+            .into_iter()
+            .fold(self, |builder, origin| builder.with_origin(origin))
+    }
+
+    /// Allows every origin
+    ///
+    /// Responses carry `Access-Control-Allow-Origin: *`. Combining this with credentials is
+    /// rejected by `build`, since it would let any site make credentialed requests.
+    ///
+    /// # Returns
+    /// Self allowing any origin for chaining
+    pub fn with_any_origin(mut self) -> Self {
+        self.policy.origins = AllowedOrigins::Any;
+        self
+    }
+
+    /// Sets the allowed methods, replacing the defaults
+    ///
+    /// # Parameters
+    /// * `methods` - Method names, compared case-sensitively as HTTP requires
+    ///
+    /// # Returns
+    /// Self with the methods set for chaining
+    pub fn with_methods<I, M>(mut self, methods: I) -> Self
+    where
+        I: IntoIterator<Item = M>,
+        M: Into<String>,
+    {
+        self.policy.methods = methods.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the request headers allowed on top of the CORS-safelisted ones
+    ///
+    /// # Parameters
+    /// * `headers` - Header names, compared case-insensitively
+    ///
+    /// # Returns
+    /// Self with the headers set for chaining
+    pub fn with_headers<I, H>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = H>,
+        H: Into<String>,
+    {
+        self.policy.headers = AllowedHeaders::List(
+            headers
+                .into_iter()
+                .map(|h| h.into().to_ascii_lowercase())
+                .collect(),
+        );
+        self
+    }
+
+    /// Allows any request header by echoing the requested headers on preflight
+    ///
+    /// # Returns
+    /// Self allowing any header for chaining
+    pub fn with_any_header(mut self) -> Self {
+        self.policy.headers = AllowedHeaders::Any;
+        self
+    }
+
+    /// Sets the response headers exposed to scripts
+    ///
+    /// # Parameters
+    /// * `headers` - Header names
+    ///
+    /// # Returns
+    /// Self with the exposed headers set for chaining
+    pub fn with_expose_headers<I, H>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = H>,
+        H: Into<String>,
+    {
+        self.policy.expose_headers = headers.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets how long browsers may cache a preflight response
+    ///
+    /// # Parameters
+    /// * `max_age` - The cache duration, truncated to whole seconds
+    ///
+    /// # Returns
+    /// Self with the max age set for chaining
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.policy.max_age = Some(max_age);
+        self
+    }
+
+    /// Sets whether credentials (cookies, authorization) may be sent
+    ///
+    /// # Parameters
+    /// * `credentials` - Whether to send `Access-Control-Allow-Credentials: true`
+    ///
+    /// # Returns
+    /// Self with the flag set for chaining
+    pub fn with_credentials(mut self, credentials: bool) -> Self {
+        self.policy.credentials = credentials;
+        self
+    }
+
+    /// Builds and returns the final CorsPolicy instance
+    ///
+    /// # Returns
+    /// A CorsPolicy with all the configured settings, or an `Errorsx` if any origin is allowed
+    /// together with credentials
+    #[track_caller]
+    pub fn build(mut self) -> Result<CorsPolicy, Errorsx> {
+        if self.policy.origins == AllowedOrigins::Any && self.policy.credentials {
+            return Err(Errorsx::builder("Invalid CORS policy")
+                .with_context("Credentials cannot be allowed for any origin; list the origins")
+                .build());
+        }
+        if self.policy.methods.is_empty() {
+            self.policy.methods = DEFAULT_METHODS.iter().map(|m| m.to_string()).collect();
+        }
+        Ok(self.policy)
+    }
+}
+
+/// A CORS policy
+///
+/// # Fields
+/// * `origins` - Allowed origins
+/// * `methods` - Allowed methods
+/// * `headers` - Allowed request headers
+/// * `expose_headers` - Response headers exposed to scripts
+/// * `max_age` - Optional preflight cache duration
+/// * `credentials` - Whether credentials are allowed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorsPolicy {
+    origins: AllowedOrigins,
+    methods: Vec<String>,
+    headers: AllowedHeaders,
+    expose_headers: Vec<String>,
+    max_age: Option<Duration>,
+    credentials: bool,
+}
+
+impl CorsPolicy {
+    /// Creates a new CorsPolicyBuilder to construct a policy
+    ///
+    /// # Returns
+    /// A CorsPolicyBuilder instance for fluent construction
+    pub fn builder() -> CorsPolicyBuilder {
+        CorsPolicyBuilder::new()
+    }
+
+    /// Evaluates an actual (non-preflight) request
+    ///
+    /// # Parameters
+    /// * `request_origin` - The value of the `Origin` header, if present
+    /// * `method` - The request method
+    ///
+    /// # Returns
+    /// The CorsDecision with the headers to add to the response
+    pub fn evaluate(&self, request_origin: Option<&str>, method: &str) -> CorsDecision {
+        let Some(origin) = request_origin else {
+            return CorsDecision::NotCors(self.vary());
+        };
+        if let Err(denial) = self.check_origin(origin) {
+            return CorsDecision::Denied(denial, self.vary());
+        }
+        if let Err(denial) = self.check_method(method) {
+            return CorsDecision::Denied(denial, self.vary());
+        }
+
+        let mut headers = self.origin_headers(origin);
+        headers.extend(self.vary());
+        if !self.expose_headers.is_empty() {
+            headers.push((header::EXPOSE_HEADERS, self.expose_headers.join(", ")));
+        }
+        CorsDecision::Allowed(headers)
+    }
+
+    /// Evaluates a preflight (`OPTIONS`) request
+    ///
+    /// # Parameters
+    /// * `request_origin` - The value of the `Origin` header, if present
+    /// * `requested_method` - The value of `Access-Control-Request-Method`
+    /// * `requested_headers` - The value of `Access-Control-Request-Headers`, if present
+    ///
+    /// # Returns
+    /// The CorsDecision with the headers for the preflight response
+    pub fn evaluate_preflight(
+        &self,
+        request_origin: Option<&str>,
+        requested_method: &str,
+        requested_headers: Option<&str>,
+    ) -> CorsDecision {
+        let Some(origin) = request_origin else {
+            return CorsDecision::NotCors(preflight_vary());
+        };
+        if let Err(denial) = self.check_origin(origin) {
+            return CorsDecision::Denied(denial, preflight_vary());
+        }
+        if let Err(denial) = self.check_method(requested_method) {
+            return CorsDecision::Denied(denial, preflight_vary());
+        }
+
+        let requested: Vec<String> = requested_headers
+            .unwrap_or_default()
+            .split(',')
+            .map(|h| h.trim().to_ascii_lowercase())
+            .filter(|h| !h.is_empty())
+            .collect();
+        if let AllowedHeaders::List(allowed) = &self.headers {
+            if let Some(rejected) = requested.iter().find(|h| !allowed.contains(h)) {
+                return CorsDecision::Denied(
+                    CorsDenial::HeaderNotAllowed(rejected.clone()),
+                    preflight_vary(),
+                );
+            }
+        }
+
+        let mut headers = self.origin_headers(origin);
+        headers.push((header::ALLOW_METHODS, self.methods.join(", ")));
+        let allow_headers = match &self.headers {
+            AllowedHeaders::Any => requested.join(", "),
+            AllowedHeaders::List(allowed) => allowed.join(", "),
+        };
+        if !allow_headers.is_empty() {
+            headers.push((header::ALLOW_HEADERS, allow_headers));
+        }
+        if let Some(max_age) = self.max_age {
+            headers.push((header::MAX_AGE, max_age.as_secs().to_string()));
+        }
+        headers.extend(preflight_vary());
+        CorsDecision::Allowed(headers)
+    }
+
+    fn check_origin(&self, origin: &str) -> Result<(), CorsDenial> {
+        let normalized = origin.trim().to_ascii_lowercase();
+        let allowed = match &self.origins {
+            AllowedOrigins::Any => true,
+            AllowedOrigins::List(patterns) => patterns.iter().any(|p| p.matches(&normalized)),
+        };
+        if allowed {
+            Ok(())
+        } else {
+            Err(CorsDenial::OriginNotAllowed(origin.to_string()))
+        }
+    }
+
+    fn check_method(&self, method: &str) -> Result<(), CorsDenial> {
+        if self.methods.iter().any(|m| m == method) {
+            Ok(())
+        } else {
+            Err(CorsDenial::MethodNotAllowed(method.to_string()))
+        }
+    }
+
+    /// Whether the request origin is echoed back rather than answered with `*`
+    fn reflects_origin(&self) -> bool {
+        self.origins != AllowedOrigins::Any
+    }
+
+    /// The `Vary` header for actual requests, needed whenever the response depends on the origin
+    fn vary(&self) -> Vec<(&'static str, String)> {
+        if self.reflects_origin() {
+            vec![(header::VARY, "Origin".to_string())]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Origin and credentials headers shared by actual and preflight responses
+    fn origin_headers(&self, origin: &str) -> Vec<(&'static str, String)> {
+        let mut headers = Vec::new();
+        if self.reflects_origin() {
+            headers.push((header::ALLOW_ORIGIN, origin.to_string()));
+        } else {
+            headers.push((header::ALLOW_ORIGIN, "*".to_string()));
+        }
+        if self.credentials {
+            headers.push((header::ALLOW_CREDENTIALS, "true".to_string()));
+        }
+        headers
+    }
+}
+
+/// The `Vary` header for preflight responses, which depend on the origin and requested access
+fn preflight_vary() -> Vec<(&'static str, String)> {
+    vec![(
+        header::VARY,
+        "Origin, Access-Control-Request-Method, Access-Control-Request-Headers".to_string(),
+    )]
+}
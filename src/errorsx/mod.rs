@@ -9,6 +9,7 @@
 //! The main components are:
 //! - `Errorsx`: The core error type that holds all error details
 //! - `ErrorsxBuilder`: A builder pattern implementation for constructing errors
+//! - `ErrorsxGroup`: A collection of errors reported together
 //!
 //! ## Usage Scenarios
 //!
@@ -255,3 +256,114 @@ impl Errorsx {
         &self.status
     }
 }
+
+/// A collection of errors reported together
+///
+/// Used where several independent operations can fail, such as concurrent tasks, so that every
+/// failure is reported instead of only the first one.
+///
+/// # Fields
+/// * `errors` - The collected errors, in the order they were added
+#[derive(Debug, Default)]
+pub struct ErrorsxGroup {
+    errors: Vec<Errorsx>,
+}
+
+/// Display implementation for ErrorsxGroup
+///
+/// Formats the number of errors followed by each error message on its own line
+impl Display for ErrorsxGroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} error(s) occurred:", self.errors.len())?;
+        for error in &self.errors {
+            write!(f, "\n - {}", error.message())?;
+        }
+        Ok(())
+    }
+}
+
+/// Error implementation for ErrorsxGroup
+///
+/// Exposes the first collected error as the source
+impl Error for ErrorsxGroup {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.errors.first().map(|e| e as &(dyn Error + 'static))
+    }
+}
+
+impl From<Errorsx> for ErrorsxGroup {
+    fn from(error: Errorsx) -> Self {
+        Self {
+            errors: vec![error],
+        }
+    }
+}
+
+impl From<Vec<Errorsx>> for ErrorsxGroup {
+    fn from(errors: Vec<Errorsx>) -> Self {
+        Self { errors }
+    }
+}
+
+impl IntoIterator for ErrorsxGroup {
+    type Item = Errorsx;
+    type IntoIter = std::vec::IntoIter<Errorsx>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.errors.into_iter()
+    }
+}
+
+impl ErrorsxGroup {
+    /// Creates a new empty ErrorsxGroup
+    ///
+    /// # Returns
+    /// A new ErrorsxGroup without any errors
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an error to the group
+    ///
+    /// # Parameters
+    /// * `error` - The error to add
+    pub fn push(&mut self, error: Errorsx) {
+        self.errors.push(error);
+    }
+
+    /// Gets the collected errors
+    ///
+    /// # Returns
+    /// A slice of the errors in the order they were added
+    pub fn errors(&self) -> &[Errorsx] {
+        &self.errors
+    }
+
+    /// Gets the number of collected errors
+    ///
+    /// # Returns
+    /// The number of errors in the group
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Checks whether the group holds no errors
+    ///
+    /// # Returns
+    /// True if no errors have been added
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Converts the group into a Result
+    ///
+    /// # Returns
+    /// `Ok(())` if the group is empty, otherwise `Err` with the group itself
+    pub fn into_result(self) -> Result<(), ErrorsxGroup> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
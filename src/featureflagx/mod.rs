@@ -0,0 +1,515 @@
+//! # Featureflagx
+//!
+//! This module provides feature flag evaluation without a third-party SDK. Flags are plain
+//! configuration: a flag is either switched on or off, rolled out to a stable percentage of
+//! users, or targeted at users with matching attributes. Flags can be loaded once or watched,
+//! in which case edits to the flag file take effect without a restart.
+//!
+//! ## Overview
+//!
+//! The main components are:
+//! - `FlagProvider`: Trait answering `is_enabled(flag, context)`, for custom backends
+//! - `ConfigFlagProvider`: Built-in provider reading flags from a configuration file
+//! - `Flag` / `Rule`: A flag definition and its attribute-match rules
+//! - `FlagContext`: The user or request a flag is evaluated for
+//!
+//! ## Evaluation
+//!
+//! 1. A disabled flag is off for everyone
+//! 2. Rules are checked in order; the first rule whose attribute matches decides
+//! 3. With a rollout percentage, the context key is hashed together with the flag name into a
+//!    bucket, so a user stays in or out of the rollout across restarts and machines; contexts
+//!    without a key are excluded
+//! 4. Otherwise the flag is on
+//!
+//! Unknown flags are off. Flag names are case-insensitive. Unknown fields in a flag or rule fail
+//! the load, so a misspelled field cannot silently leave a flag on.
+//!
+//! ## Configuration
+//!
+//! Flags live under a `flags` table in any format supported by the `config` crate:
+//!
+//! ```toml
+//! [flags]
+//! new_checkout = true
+//!
+//! [flags.search_v2]
+//! rollout = 25
+//!
+//! [[flags.search_v2.rules]]
+//! attribute = "plan"
+//! values = ["enterprise"]
+//! ```
+//!
+//! ### Example
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use x::featureflagx::{ConfigFlagProvider, FlagContext, FlagProvider};
+//!
+//! # async fn run() -> Result<(), x::errorsx::Errorsx> {
+//! let flags = ConfigFlagProvider::watch("flags.toml", Duration::from_millis(200))?;
+//!
+//! let context = FlagContext::new("user-42").with_attribute("plan", "enterprise");
+//! if flags.is_enabled("search_v2", &context) {
+//!     // serve the new search
+//! }
+//!
+//! // React to edits of flags.toml
+//! if let Some(mut changes) = flags.subscribe() {
+//!     changes.changed().await.ok();
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tokio::sync::watch;
+
+use crate::{
+    errorsx::Errorsx,
+    watchx::{self, Reloadable},
+};
+
+/// Flag definitions by lowercase name
+pub type Flags = HashMap<String, Flag>;
+
+/// Number of rollout buckets; percentages are accurate to two decimal places
+const ROLLOUT_BUCKETS: u64 = 10_000;
+
+/// The user or request a flag is evaluated for
+///
+/// # Fields
+/// * `key` - A stable identifier such as a user or account ID, used for percentage rollouts
+/// * `attributes` - Attributes matched by rules, e.g. `plan` or `country`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlagContext {
+    key: Option<String>,
+    attributes: HashMap<String, String>,
+}
+
+impl FlagContext {
+    /// Creates a new FlagContext with a stable key
+    ///
+    /// # Parameters
+    /// * `key` - The stable identifier, e.g. a user ID
+    ///
+    /// # Returns
+    /// A new FlagContext
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: Some(key.into()),
+            attributes: HashMap::new(),
+        }
+    }
+
+    /// Creates a new FlagContext without a key
+    ///
+    /// Percentage rollouts are off for anonymous contexts.
+    ///
+    /// # Returns
+    /// A new FlagContext
+    pub fn anonymous() -> Self {
+        Self::default()
+    }
+
+    /// Adds an attribute for rules to match
+    ///
+    /// # Parameters
+    /// * `name` - The attribute name
+    /// * `value` - The attribute value
+    ///
+    /// # Returns
+    /// The FlagContext instance
+    pub fn with_attribute(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.attributes.insert(name.into(), value.into());
+        self
+    }
+
+    /// Gets the stable key
+    ///
+    /// # Returns
+    /// The key, if any
+    pub fn key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+
+    /// Gets an attribute
+    ///
+    /// # Parameters
+    /// * `name` - The attribute name
+    ///
+    /// # Returns
+    /// The attribute value, if set
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(String::as_str)
+    }
+}
+
+/// A rule turning a flag on or off for contexts with matching attributes
+///
+/// # Fields
+/// * `attribute` - The attribute to check
+/// * `values` - The values that match
+/// * `enabled` - The result when the rule matches (default: `true`)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    pub attribute: String,
+    pub values: Vec<String>,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+}
+
+impl Rule {
+    /// Creates a rule turning the flag on for matching contexts
+    ///
+    /// # Parameters
+    /// * `attribute` - The attribute to check
+    /// * `values` - The values that match
+    ///
+    /// # Returns
+    /// A new Rule
+    pub fn allow<I, S>(attribute: impl Into<String>, values: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            attribute: attribute.into(),
+            values: values.into_iter().map(Into::into).collect(),
+            enabled: true,
+        }
+    }
+
+    /// Creates a rule turning the flag off for matching contexts
+    ///
+    /// # Parameters
+    /// * `attribute` - The attribute to check
+    /// * `values` - The values that match
+    ///
+    /// # Returns
+    /// A new Rule
+    pub fn deny<I, S>(attribute: impl Into<String>, values: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            enabled: false,
+            ..Self::allow(attribute, values)
+        }
+    }
+
+    /// Checks whether the rule applies to a context
+    fn matches(&self, context: &FlagContext) -> bool {
+        context
+            .attribute(&self.attribute)
+            .is_some_and(|value| self.values.iter().any(|candidate| candidate == value))
+    }
+}
+
+/// A feature flag definition
+///
+/// In configuration a flag is written either as a bool or as a table with the fields below.
+///
+/// # Fields
+/// * `enabled` - Master switch; a disabled flag is off for everyone (default: `true`)
+/// * `rollout` - Percentage of keyed contexts the flag is on for, from 0 to 100
+/// * `rules` - Attribute rules checked before the rollout
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(from = "FlagSpec")]
+pub struct Flag {
+    pub enabled: bool,
+    pub rollout: Option<f64>,
+    pub rules: Vec<Rule>,
+}
+
+impl Flag {
+    /// Creates a flag that is on or off for everyone
+    ///
+    /// # Parameters
+    /// * `enabled` - Whether the flag is on
+    ///
+    /// # Returns
+    /// A new Flag
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            rollout: None,
+            rules: Vec::new(),
+        }
+    }
+
+    /// Limits the flag to a percentage of keyed contexts
+    ///
+    /// # Parameters
+    /// * `percentage` - The rollout percentage, clamped to 0..=100
+    ///
+    /// # Returns
+    /// The Flag instance
+    pub fn with_rollout(mut self, percentage: f64) -> Self {
+        self.rollout = Some(percentage);
+        self
+    }
+
+    /// Adds an attribute rule
+    ///
+    /// # Parameters
+    /// * `rule` - The rule, checked after previously added rules
+    ///
+    /// # Returns
+    /// The Flag instance
+    pub fn with_rule(mut self, rule: Rule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Evaluates the flag for a context
+    ///
+    /// # Parameters
+    /// * `name` - The flag name, which seeds the rollout hash
+    /// * `context` - The context to evaluate for
+    ///
+    /// # Returns
+    /// Whether the flag is on
+    pub fn evaluate(&self, name: &str, context: &FlagContext) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        if let Some(rule) = self.rules.iter().find(|rule| rule.matches(context)) {
+            return rule.enabled;
+        }
+        match (self.rollout, context.key()) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(percentage), Some(key)) => {
+                let threshold = (percentage.clamp(0.0, 100.0) * 100.0).round() as u64;
+                rollout_bucket(name, key) < threshold
+            }
+        }
+    }
+}
+
+/// A flag as written in configuration
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FlagSpec {
+    Switch(bool),
+    Definition(FlagDefinition),
+}
+
+/// A flag written as a table
+///
+/// Unknown fields are rejected so that a misspelled `enabled` fails the load instead of leaving
+/// the flag on.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FlagDefinition {
+    #[serde(default = "enabled_by_default")]
+    enabled: bool,
+    #[serde(default)]
+    rollout: Option<f64>,
+    #[serde(default)]
+    rules: Vec<Rule>,
+}
+
+impl From<FlagSpec> for Flag {
+    fn from(spec: FlagSpec) -> Self {
+        match spec {
+            FlagSpec::Switch(enabled) => Flag::new(enabled),
+            FlagSpec::Definition(definition) => Flag {
+                enabled: definition.enabled,
+                rollout: definition.rollout,
+                rules: definition.rules,
+            },
+        }
+    }
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+/// Source of feature flag decisions
+pub trait FlagProvider: Send + Sync {
+    /// Checks whether a flag is on for a context
+    ///
+    /// # Parameters
+    /// * `flag` - The flag name
+    /// * `context` - The user or request to evaluate for
+    ///
+    /// # Returns
+    /// Whether the flag is on; unknown flags are off
+    fn is_enabled(&self, flag: &str, context: &FlagContext) -> bool;
+}
+
+impl FlagProvider for Flags {
+    fn is_enabled(&self, flag: &str, context: &FlagContext) -> bool {
+        let name = flag.to_lowercase();
+        self.get(&name)
+            .is_some_and(|definition| definition.evaluate(&name, context))
+    }
+}
+
+/// Where a ConfigFlagProvider gets its flags from
+#[derive(Debug)]
+enum Source {
+    Fixed(Arc<Flags>),
+    Reloading(Reloadable<Flags>),
+}
+
+/// A FlagProvider reading flags from configuration
+#[derive(Debug)]
+pub struct ConfigFlagProvider {
+    source: Source,
+}
+
+impl ConfigFlagProvider {
+    /// Creates a new ConfigFlagProvider from flag definitions
+    ///
+    /// # Parameters
+    /// * `flags` - The flags by name
+    ///
+    /// # Returns
+    /// A ConfigFlagProvider that never changes
+    pub fn new(flags: Flags) -> Self {
+        Self {
+            source: Source::Fixed(Arc::new(lowercase_names(flags))),
+        }
+    }
+
+    /// Reads the `flags` table of already loaded configuration
+    ///
+    /// # Parameters
+    /// * `config` - The configuration
+    ///
+    /// # Returns
+    /// A ConfigFlagProvider (with no flags if the table is missing), or an `Errorsx` if a flag
+    /// definition is invalid
+    #[track_caller]
+    pub fn from_config(config: &config::Config) -> Result<Self, Errorsx> {
+        Ok(Self::new(flags_from_config(config)?))
+    }
+
+    /// Loads flags from a configuration file
+    ///
+    /// The format is inferred from the file extension (TOML, YAML, JSON, ...).
+    ///
+    /// # Parameters
+    /// * `path` - The configuration file
+    ///
+    /// # Returns
+    /// A ConfigFlagProvider, or an `Errorsx` if the file could not be read or parsed
+    #[track_caller]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Errorsx> {
+        Ok(Self::new(load_flags(path.as_ref())?))
+    }
+
+    /// Loads flags from a configuration file and reloads them whenever it changes
+    ///
+    /// A failed reload is logged and the previous flags stay in effect. Must be called from
+    /// within a Tokio runtime.
+    ///
+    /// # Parameters
+    /// * `path` - The configuration file
+    /// * `debounce` - The quiet period before a changed file is re-read
+    ///
+    /// # Returns
+    /// A ConfigFlagProvider, or an `Errorsx` if the initial load or the watch setup failed
+    #[track_caller]
+    pub fn watch(path: impl AsRef<Path>, debounce: Duration) -> Result<Self, Errorsx> {
+        let reloadable = watchx::watch_and_reload_with_debounce(path, debounce, load_flags)?;
+        Ok(Self {
+            source: Source::Reloading(reloadable),
+        })
+    }
+
+    /// Gets the current flag definitions
+    ///
+    /// # Returns
+    /// A snapshot of the flags by lowercase name
+    pub fn flags(&self) -> Arc<Flags> {
+        match &self.source {
+            Source::Fixed(flags) => flags.clone(),
+            Source::Reloading(reloadable) => reloadable.current(),
+        }
+    }
+
+    /// Subscribes to flag changes
+    ///
+    /// # Returns
+    /// A receiver notified each time the flags are reloaded, or `None` if the provider was not
+    /// created with `watch`
+    pub fn subscribe(&self) -> Option<watch::Receiver<Arc<Flags>>> {
+        match &self.source {
+            Source::Fixed(_) => None,
+            Source::Reloading(reloadable) => Some(reloadable.subscribe()),
+        }
+    }
+}
+
+impl FlagProvider for ConfigFlagProvider {
+    fn is_enabled(&self, flag: &str, context: &FlagContext) -> bool {
+        self.flags().is_enabled(flag, context)
+    }
+}
+
+/// Computes the rollout bucket of a key for a flag, in `0..ROLLOUT_BUCKETS`
+fn rollout_bucket(flag: &str, key: &str) -> u64 {
+    let digest = Sha256::new()
+        .chain_update(flag.as_bytes())
+        .chain_update(b":")
+        .chain_update(key.as_bytes())
+        .finalize();
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&digest[..8]);
+    u64::from_be_bytes(prefix) % ROLLOUT_BUCKETS
+}
+
+/// Loads the flags table from a configuration file
+#[track_caller]
+fn load_flags(path: &Path) -> Result<Flags, Errorsx> {
+    let config = config::Config::builder()
+        .add_source(config::File::from(PathBuf::from(path)))
+        .build()
+        .map_err(|err| {
+            Errorsx::builder("Failed to load feature flags")
+                .with_context(format!("Path: {}", path.display()))
+                .with_source(err)
+                .build()
+        })?;
+    flags_from_config(&config).map_err(|err| {
+        Errorsx::builder("Failed to load feature flags")
+            .with_context(format!("Path: {}", path.display()))
+            .with_source(err)
+            .build()
+    })
+}
+
+/// Reads the flags table from configuration
+#[track_caller]
+fn flags_from_config(config: &config::Config) -> Result<Flags, Errorsx> {
+    match config.get::<Flags>("flags") {
+        Ok(flags) => Ok(lowercase_names(flags)),
+        Err(config::ConfigError::NotFound(_)) => Ok(Flags::new()),
+        Err(err) => Err(Errorsx::builder("Invalid feature flag definition")
+            .with_source(err)
+            .build()),
+    }
+}
+
+/// Lowercases flag names, matching how configuration keys are read
+fn lowercase_names(flags: Flags) -> Flags {
+    flags
+        .into_iter()
+        .map(|(name, flag)| (name.to_lowercase(), flag))
+        .collect()
+}
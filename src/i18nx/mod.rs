@@ -0,0 +1,419 @@
+//! # I18nx
+//!
+//! This module provides message catalogs for user-facing text. Catalogs are loaded per locale
+//! from Fluent (`.ftl`) or simple key/value files, messages are looked up along a locale
+//! fallback chain and placeholders are filled in with `stringsx::interpolate`.
+//!
+//! ## Overview
+//!
+//! The main components are:
+//! - `Catalog`: The messages of one locale
+//! - `CatalogFormat`: The supported source formats
+//! - `Localizer`: A set of catalogs with a default locale and fallback rules
+//!
+//! ## Formats
+//!
+//! Both formats consist of `key = value` lines with `#` comments.
+//! - Key/value files use `{name}` placeholders
+//! - Fluent files support the subset of Fluent made of simple messages: `{ $name }` variable
+//!   references and indented continuation lines. Terms and selectors are not supported;
+//!   attributes (`.name = value` lines) are skipped.
+//!
+//! ## Fallback
+//!
+//! A lookup for `fr-CA` tries `fr-CA`, then any explicit fallback configured for it, then `fr`,
+//! then the default locale.
+//!
+//! ### Example
+//! ```rust
+//! use x::i18nx::{Catalog, CatalogFormat, Localizer};
+//!
+//! # fn run() -> Result<(), x::errorsx::Errorsx> {
+//! let en = Catalog::parse("en", "greeting = Hello, { $name }!", CatalogFormat::Fluent)?;
+//! let fr = Catalog::parse("fr", "greeting = Bonjour, {name} !", CatalogFormat::KeyValue)?;
+//!
+//! let localizer = Localizer::new("en").with_catalog(en).with_catalog(fr);
+//! assert_eq!(
+//!     localizer.message("fr-CA", "greeting", &[("name", "Ada")]).as_deref(),
+//!     Some("Bonjour, Ada !")
+//! );
+//! # Ok(())
+//! # }
+//! # run().unwrap();
+//! ```
+
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::{errorsx::Errorsx, stringsx::interpolate::interpolate};
+
+/// Source formats a Catalog can be parsed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CatalogFormat {
+    /// Simple Fluent messages with `{ $name }` placeholders
+    Fluent,
+    /// `key = value` lines with `{name}` placeholders
+    KeyValue,
+}
+
+impl CatalogFormat {
+    /// Determines the format from a file extension
+    ///
+    /// # Parameters
+    /// * `path` - The catalog file path
+    ///
+    /// # Returns
+    /// `Fluent` for `.ftl` files, `KeyValue` otherwise
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("ftl") => CatalogFormat::Fluent,
+            _ => CatalogFormat::KeyValue,
+        }
+    }
+}
+
+/// The messages of one locale
+///
+/// # Fields
+/// * `locale` - The normalized locale tag, e.g. `pt-br`
+/// * `messages` - Message templates by key, with `{name}` placeholders
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    locale: String,
+    messages: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// Creates an empty catalog
+    ///
+    /// # Parameters
+    /// * `locale` - The locale tag, e.g. `pt-BR`
+    ///
+    /// # Returns
+    /// A new Catalog without messages
+    pub fn new(locale: &str) -> Self {
+        Self {
+            locale: normalize_locale(locale),
+            messages: HashMap::new(),
+        }
+    }
+
+    /// Parses a catalog from source text
+    ///
+    /// # Parameters
+    /// * `locale` - The locale tag, e.g. `pt-BR`
+    /// * `source` - The catalog contents
+    /// * `format` - The format of the contents
+    ///
+    /// # Returns
+    /// The parsed Catalog, or an `Errorsx` pointing at the first malformed line
+    #[track_caller]
+    pub fn parse(locale: &str, source: &str, format: CatalogFormat) -> Result<Self, Errorsx> {
+        let mut catalog = Self::new(locale);
+        let mut last_key: Option<String> = None;
+        // Whether continuation lines belong to a skipped Fluent attribute
+        let mut in_attribute = false;
+
+        for (index, line) in source.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            let continuation = line.starts_with([' ', '\t']);
+            if continuation && format == CatalogFormat::Fluent {
+                if let Some(key) = &last_key {
+                    in_attribute |= trimmed.starts_with('.');
+                    if in_attribute {
+                        continue;
+                    }
+                    let message = catalog.messages.entry(key.clone()).or_default();
+                    if !message.is_empty() {
+                        message.push('\n');
+                    }
+                    message.push_str(&fluent_placeholders(trimmed));
+                    continue;
+                }
+            }
+
+            let Some((key, value)) = trimmed.split_once('=') else {
+                return Err(Errorsx::builder("Failed to parse message catalog")
+                    .with_context(format!("Locale: {}", catalog.locale))
+                    .with_context(format!("Line {}: expected `key = value`", index + 1))
+                    .build());
+            };
+            let key = key.trim().to_string();
+            let value = value.trim();
+            let value = match format {
+                CatalogFormat::Fluent => fluent_placeholders(value),
+                CatalogFormat::KeyValue => value.to_string(),
+            };
+            catalog.messages.insert(key.clone(), value);
+            last_key = Some(key);
+            in_attribute = false;
+        }
+
+        Ok(catalog)
+    }
+
+    /// Loads a catalog from a file, picking the format from the extension
+    ///
+    /// # Parameters
+    /// * `locale` - The locale tag, e.g. `pt-BR`
+    /// * `path` - The catalog file
+    ///
+    /// # Returns
+    /// The loaded Catalog, or an `Errorsx` if the file could not be read or parsed
+    #[track_caller]
+    pub fn load(locale: &str, path: impl AsRef<Path>) -> Result<Self, Errorsx> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path).map_err(|err| {
+            Errorsx::builder("Failed to read message catalog")
+                .with_context(format!("Path: {}", path.display()))
+                .with_source(err)
+                .build()
+        })?;
+        Self::parse(locale, &source, CatalogFormat::from_path(path))
+    }
+
+    /// Adds or replaces a message
+    ///
+    /// # Parameters
+    /// * `key` - The message key
+    /// * `template` - The message with `{name}` placeholders
+    ///
+    /// # Returns
+    /// Self with the message added for chaining
+    pub fn with_message(mut self, key: impl Into<String>, template: impl Into<String>) -> Self {
+        self.messages.insert(key.into(), template.into());
+        self
+    }
+
+    /// Gets the normalized locale tag
+    ///
+    /// # Returns
+    /// The locale, lowercase with `-` separators
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    /// Gets a message template without interpolation
+    ///
+    /// # Parameters
+    /// * `key` - The message key
+    ///
+    /// # Returns
+    /// The template, or `None` if the catalog has no such message
+    pub fn template(&self, key: &str) -> Option<&str> {
+        self.messages.get(key).map(String::as_str)
+    }
+}
+
+/// A set of catalogs with a default locale and fallback rules
+///
+/// # Fields
+/// * `catalogs` - Catalogs by normalized locale
+/// * `default_locale` - Locale tried last for every lookup
+/// * `fallbacks` - Explicit fallback locale by normalized locale
+#[derive(Debug, Clone)]
+pub struct Localizer {
+    catalogs: HashMap<String, Catalog>,
+    default_locale: String,
+    fallbacks: HashMap<String, String>,
+}
+
+impl Localizer {
+    /// Creates a Localizer without catalogs
+    ///
+    /// # Parameters
+    /// * `default_locale` - Locale tried last for every lookup
+    ///
+    /// # Returns
+    /// A new Localizer
+    pub fn new(default_locale: &str) -> Self {
+        Self {
+            catalogs: HashMap::new(),
+            default_locale: normalize_locale(default_locale),
+            fallbacks: HashMap::new(),
+        }
+    }
+
+    /// Loads every catalog in a directory
+    ///
+    /// Files are named after their locale, e.g. `en.ftl` or `pt-BR.properties`; the format is
+    /// picked from the extension.
+    ///
+    /// # Parameters
+    /// * `dir` - The directory containing catalog files
+    /// * `default_locale` - Locale tried last for every lookup
+    ///
+    /// # Returns
+    /// A Localizer with all catalogs, or an `Errorsx` if any file could not be loaded
+    #[track_caller]
+    pub fn load_dir(dir: impl AsRef<Path>, default_locale: &str) -> Result<Self, Errorsx> {
+        let dir = dir.as_ref();
+        let read_error = |err| {
+            Errorsx::builder("Failed to read message catalog directory")
+                .with_context(format!("Path: {}", dir.display()))
+                .with_source(err)
+                .build()
+        };
+
+        let mut localizer = Self::new(default_locale);
+        for entry in fs::read_dir(dir).map_err(read_error)? {
+            let path = entry.map_err(read_error)?.path();
+            if !path.is_file() {
+                continue;
+            }
+            if let Some(locale) = path.file_stem().and_then(|stem| stem.to_str()) {
+                let catalog = Catalog::load(locale, &path)?;
+                localizer = localizer.with_catalog(catalog);
+            }
+        }
+        Ok(localizer)
+    }
+
+    /// Adds a catalog, merging it into an existing catalog of the same locale
+    ///
+    /// # Parameters
+    /// * `catalog` - The catalog to add
+    ///
+    /// # Returns
+    /// Self with the catalog added for chaining
+    pub fn with_catalog(mut self, catalog: Catalog) -> Self {
+        match self.catalogs.get_mut(&catalog.locale) {
+            Some(existing) => existing.messages.extend(catalog.messages),
+            None => {
+                self.catalogs.insert(catalog.locale.clone(), catalog);
+            }
+        }
+        self
+    }
+
+    /// Sets an explicit fallback for a locale
+    ///
+    /// # Parameters
+    /// * `locale` - The locale to configure, e.g. `pt-BR`
+    /// * `fallback` - The locale tried after it, e.g. `pt-PT`
+    ///
+    /// # Returns
+    /// Self with the fallback set for chaining
+    pub fn with_fallback(mut self, locale: &str, fallback: &str) -> Self {
+        self.fallbacks
+            .insert(normalize_locale(locale), normalize_locale(fallback));
+        self
+    }
+
+    /// Computes the locales tried for a lookup, most specific first
+    ///
+    /// # Parameters
+    /// * `locale` - The requested locale
+    ///
+    /// # Returns
+    /// The normalized locales without duplicates, ending with the default locale
+    pub fn fallback_chain(&self, locale: &str) -> Vec<String> {
+        fn push(chain: &mut Vec<String>, candidate: String) {
+            if !candidate.is_empty() && !chain.contains(&candidate) {
+                chain.push(candidate);
+            }
+        }
+
+        let mut chain: Vec<String> = Vec::new();
+        let mut next = Some(normalize_locale(locale));
+        while let Some(current) = next.take() {
+            if chain.contains(&current) {
+                break;
+            }
+            push(&mut chain, current.clone());
+            if let Some(fallback) = self.fallbacks.get(&current) {
+                push(&mut chain, fallback.clone());
+            }
+            next = current
+                .rsplit_once('-')
+                .map(|(parent, _)| parent.to_string());
+        }
+        push(&mut chain, self.default_locale.clone());
+        chain
+    }
+
+    /// Resolves and interpolates a message
+    ///
+    /// # Parameters
+    /// * `locale` - The requested locale
+    /// * `key` - The message key
+    /// * `args` - Placeholder values
+    ///
+    /// # Returns
+    /// The message from the first catalog in the fallback chain that has it, or `None`
+    pub fn message(&self, locale: &str, key: &str, args: &[(&str, &str)]) -> Option<String> {
+        self.fallback_chain(locale)
+            .iter()
+            .filter_map(|candidate| self.catalogs.get(candidate))
+            .find_map(|catalog| catalog.template(key))
+            .map(|template| interpolate(template, args))
+    }
+
+    /// Resolves and interpolates a message, falling back to the key itself
+    ///
+    /// # Parameters
+    /// * `locale` - The requested locale
+    /// * `key` - The message key
+    /// * `args` - Placeholder values
+    ///
+    /// # Returns
+    /// The message, or the key if no catalog has it
+    pub fn message_or_key(&self, locale: &str, key: &str, args: &[(&str, &str)]) -> String {
+        self.message(locale, key, args)
+            .unwrap_or_else(|| key.to_string())
+    }
+
+    /// Localizes the message of an error for display to users
+    ///
+    /// The error message is used as the message key, so errors can be created with keys such as
+    /// `Errorsx::new("upload-too-large")` and translated at the edge.
+    ///
+    /// # Parameters
+    /// * `error` - The error to localize
+    /// * `locale` - The requested locale
+    /// * `args` - Placeholder values
+    ///
+    /// # Returns
+    /// The localized message, or the error message itself if no catalog has it
+    pub fn error_message(&self, error: &Errorsx, locale: &str, args: &[(&str, &str)]) -> String {
+        self.message_or_key(locale, error.message(), args)
+    }
+}
+
+/// Lowercases a locale tag and uses `-` as the separator
+fn normalize_locale(locale: &str) -> String {
+    locale.trim().replace('_', "-").to_ascii_lowercase()
+}
+
+/// Rewrites Fluent `{ $name }` references as `{name}` placeholders
+fn fluent_placeholders(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        match tail.find('}') {
+            Some(end) => {
+                let inner = tail[1..end].trim();
+                match inner.strip_prefix('$') {
+                    Some(name) => {
+                        result.push('{');
+                        result.push_str(name.trim());
+                        result.push('}');
+                    }
+                    None => result.push_str(&tail[..=end]),
+                }
+                rest = &tail[end + 1..];
+            }
+            None => {
+                result.push_str(tail);
+                rest = "";
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
@@ -0,0 +1,415 @@
+//! # Idempotencyx
+//!
+//! This module provides shared semantics for endpoints honouring an `Idempotency-Key` header.
+//! A client may retry a request with the same key; the first attempt is processed and its
+//! response is stored, later attempts receive the stored response instead of repeating side
+//! effects. Reusing a key with a different payload is rejected as a conflict.
+//!
+//! ## Overview
+//!
+//! The main components are:
+//! - `IdempotencyStore`: Trait for storage backends (check, record, complete, release)
+//! - `MemoryIdempotencyStore`: An in-memory implementation with a time-to-live per key
+//! - `Fingerprint`: A SHA-256 digest of the request body used for conflict detection
+//! - `IdempotencyState`: The state of a key as seen by the current request
+//! - `StoredResponse`: The response replayed for completed keys
+//!
+//! ## Flow
+//!
+//! 1. Call `record` with the key and the body fingerprint
+//! 2. `New` means this request owns the key: process it, then call `complete` (or `release`
+//!    if processing failed and the client should be allowed to retry)
+//! 3. `InProgress` means another attempt is still running: respond with 409 Conflict
+//! 4. `Completed` carries the stored response: replay it
+//!
+//! A fingerprint mismatch results in an `Errorsx` with status code 422.
+//!
+//! ### Example
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use x::idempotencyx::{
+//!     fingerprint, IdempotencyState, IdempotencyStore, MemoryIdempotencyStore, StoredResponse,
+//! };
+//!
+//! # async fn run(key: &str, body: &[u8]) -> Result<(), x::errorsx::Errorsx> {
+//! let store = MemoryIdempotencyStore::new(Duration::from_secs(24 * 60 * 60));
+//! let print = fingerprint(body);
+//!
+//! match store.record(key, &print).await? {
+//!     IdempotencyState::New => {
+//!         let response = StoredResponse::new(201, b"created".to_vec());
+//!         store.complete(key, &print, response).await?;
+//!     }
+//!     IdempotencyState::InProgress => { /* respond 409 */ }
+//!     IdempotencyState::Completed(stored) => { /* replay stored */ }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::HashMap,
+    fmt::{Display, Write},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+
+use crate::errorsx::Errorsx;
+
+/// Number of stored keys below which `record` never sweeps expired keys
+const MIN_SWEEP_AT: usize = 1024;
+
+/// Expiry offset used when the TTL is too large to represent as an `Instant`
+const FAR_FUTURE: Duration = Duration::from_secs(86_400 * 365 * 30);
+
+/// A digest of a request payload
+///
+/// Two requests using the same idempotency key must have equal fingerprints.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Fingerprint(String);
+
+impl Fingerprint {
+    /// Gets the fingerprint as a lowercase hex string
+    ///
+    /// # Returns
+    /// The hex-encoded digest
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for Fingerprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Computes the fingerprint of a request body
+///
+/// # Parameters
+/// * `body` - The raw request body
+///
+/// # Returns
+/// The SHA-256 digest of the body as a `Fingerprint`
+pub fn fingerprint(body: &[u8]) -> Fingerprint {
+    let digest = Sha256::digest(body);
+    let mut hex = String::with_capacity(digest.len() * 2);
+    for byte in digest {
+        let _ = write!(hex, "{:02x}", byte);
+    }
+    Fingerprint(hex)
+}
+
+/// A response stored for a completed idempotency key
+///
+/// # Fields
+/// * `status_code` - HTTP status code of the original response
+/// * `headers` - Headers to replay, as name/value pairs
+/// * `body` - Body of the original response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredResponse {
+    pub status_code: u32,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl StoredResponse {
+    /// Creates a new StoredResponse without headers
+    ///
+    /// # Parameters
+    /// * `status_code` - HTTP status code of the original response
+    /// * `body` - Body of the original response
+    ///
+    /// # Returns
+    /// A new StoredResponse
+    pub fn new(status_code: u32, body: Vec<u8>) -> Self {
+        Self {
+            status_code,
+            headers: Vec::new(),
+            body,
+        }
+    }
+
+    /// Adds a header to replay with the response
+    ///
+    /// # Parameters
+    /// * `name` - Header name
+    /// * `value` - Header value
+    ///
+    /// # Returns
+    /// Self with the header added for chaining
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+/// The state of an idempotency key as seen by the current request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdempotencyState {
+    /// The key is unknown; after `record` the current request owns it
+    New,
+    /// Another request with the same key is still being processed
+    InProgress,
+    /// A request with the same key completed with the stored response
+    Completed(StoredResponse),
+}
+
+/// Storage backend for idempotency keys
+///
+/// Implementations must treat `record` as an atomic insert-if-absent so that concurrent
+/// requests with the same key cannot both observe `New`.
+#[async_trait]
+pub trait IdempotencyStore: Send + Sync {
+    /// Looks up a key without claiming it
+    ///
+    /// # Parameters
+    /// * `key` - The idempotency key
+    /// * `fingerprint` - Fingerprint of the current request body
+    ///
+    /// # Returns
+    /// The key's state, or an `Errorsx` with status code 422 if the fingerprint does not match
+    async fn check(
+        &self,
+        key: &str,
+        fingerprint: &Fingerprint,
+    ) -> Result<IdempotencyState, Errorsx>;
+
+    /// Claims a key for the current request if it is unknown
+    ///
+    /// # Parameters
+    /// * `key` - The idempotency key
+    /// * `fingerprint` - Fingerprint of the current request body
+    ///
+    /// # Returns
+    /// `New` if the key was claimed, otherwise its existing state, or an `Errorsx` with status
+    /// code 422 if the fingerprint does not match
+    async fn record(
+        &self,
+        key: &str,
+        fingerprint: &Fingerprint,
+    ) -> Result<IdempotencyState, Errorsx>;
+
+    /// Stores the response for a claimed key
+    ///
+    /// # Parameters
+    /// * `key` - The idempotency key
+    /// * `fingerprint` - Fingerprint of the current request body
+    /// * `response` - The response to replay for later requests
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an `Errorsx` if the key was not recorded or the fingerprint does
+    /// not match
+    async fn complete(
+        &self,
+        key: &str,
+        fingerprint: &Fingerprint,
+        response: StoredResponse,
+    ) -> Result<(), Errorsx>;
+
+    /// Forgets a key that is still in progress so the client may retry
+    ///
+    /// Completed keys are left untouched.
+    ///
+    /// # Parameters
+    /// * `key` - The idempotency key
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or an `Errorsx` if the backend failed
+    async fn release(&self, key: &str) -> Result<(), Errorsx>;
+}
+
+/// A stored key together with its payload fingerprint and expiry
+#[derive(Debug)]
+struct Entry {
+    fingerprint: Fingerprint,
+    response: Option<StoredResponse>,
+    expires_at: Instant,
+}
+
+impl Entry {
+    fn state(&self) -> IdempotencyState {
+        match &self.response {
+            Some(response) => IdempotencyState::Completed(response.clone()),
+            None => IdempotencyState::InProgress,
+        }
+    }
+}
+
+/// In-memory `IdempotencyStore` with a time-to-live per key
+///
+/// Keys expire `ttl` after they were recorded. Expired keys are removed on access, by a sweep
+/// in `record` whenever the number of stored keys has doubled since the last sweep, or
+/// explicitly with `purge_expired`. State is local to the process.
+///
+/// # Fields
+/// * `entries` - Stored keys
+/// * `sweep_at` - Number of stored keys at which `record` sweeps expired keys
+/// * `ttl` - Time-to-live of a key
+#[derive(Debug)]
+pub struct MemoryIdempotencyStore {
+    entries: Mutex<HashMap<String, Entry>>,
+    sweep_at: AtomicUsize,
+    ttl: Duration,
+}
+
+impl MemoryIdempotencyStore {
+    /// Creates a new MemoryIdempotencyStore
+    ///
+    /// # Parameters
+    /// * `ttl` - How long a key is remembered after being recorded
+    ///
+    /// # Returns
+    /// An empty store
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            sweep_at: AtomicUsize::new(MIN_SWEEP_AT),
+            ttl,
+        }
+    }
+
+    /// Removes all expired keys
+    ///
+    /// # Returns
+    /// The number of keys removed
+    pub fn purge_expired(&self) -> usize {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let before = entries.len();
+        entries.retain(|_, entry| entry.expires_at > now);
+        before - entries.len()
+    }
+
+    /// Gets the number of stored keys, including expired ones not yet purged
+    ///
+    /// # Returns
+    /// The number of keys
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Checks whether the store holds no keys
+    ///
+    /// # Returns
+    /// True if no keys are stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait]
+impl IdempotencyStore for MemoryIdempotencyStore {
+    async fn check(
+        &self,
+        key: &str,
+        fingerprint: &Fingerprint,
+    ) -> Result<IdempotencyState, Errorsx> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        match live_entry(&mut entries, key) {
+            Some(entry) => {
+                ensure_matches(key, &entry.fingerprint, fingerprint)?;
+                Ok(entry.state())
+            }
+            None => Ok(IdempotencyState::New),
+        }
+    }
+
+    async fn record(
+        &self,
+        key: &str,
+        fingerprint: &Fingerprint,
+    ) -> Result<IdempotencyState, Errorsx> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(entry) = live_entry(&mut entries, key) {
+            ensure_matches(key, &entry.fingerprint, fingerprint)?;
+            return Ok(entry.state());
+        }
+        // Doubling the threshold keeps sweeps amortized O(1) per recorded key
+        if entries.len() >= self.sweep_at.load(Ordering::Relaxed) {
+            let now = Instant::now();
+            entries.retain(|_, entry| entry.expires_at > now);
+            self.sweep_at
+                .store((entries.len() * 2).max(MIN_SWEEP_AT), Ordering::Relaxed);
+        }
+        entries.insert(
+            key.to_string(),
+            Entry {
+                fingerprint: fingerprint.clone(),
+                response: None,
+                expires_at: expiry(self.ttl),
+            },
+        );
+        Ok(IdempotencyState::New)
+    }
+
+    async fn complete(
+        &self,
+        key: &str,
+        fingerprint: &Fingerprint,
+        response: StoredResponse,
+    ) -> Result<(), Errorsx> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = live_entry(&mut entries, key).ok_or_else(|| {
+            Errorsx::builder("Idempotency key was not recorded")
+                .with_context(format!("Key: {}", key))
+                .with_status_code(404)
+                .with_status("Not Found")
+                .build()
+        })?;
+        ensure_matches(key, &entry.fingerprint, fingerprint)?;
+        entry.response = Some(response);
+        Ok(())
+    }
+
+    async fn release(&self, key: &str) -> Result<(), Errorsx> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries
+            .get(key)
+            .is_some_and(|entry| entry.response.is_none())
+        {
+            entries.remove(key);
+        }
+        Ok(())
+    }
+}
+
+/// Gets the entry for a key, dropping it first if it has expired
+fn live_entry<'a>(entries: &'a mut HashMap<String, Entry>, key: &str) -> Option<&'a mut Entry> {
+    if entries
+        .get(key)
+        .is_some_and(|entry| entry.expires_at <= Instant::now())
+    {
+        entries.remove(key);
+    }
+    entries.get_mut(key)
+}
+
+/// Fails with a 422 error if the stored and current fingerprints differ
+#[track_caller]
+fn ensure_matches(key: &str, stored: &Fingerprint, current: &Fingerprint) -> Result<(), Errorsx> {
+    if stored == current {
+        return Ok(());
+    }
+    Err(
+        Errorsx::builder("Idempotency key reused with a different request payload")
+            .with_context(format!("Key: {}", key))
+            .with_context(format!("Expected fingerprint: {}", stored))
+            .with_context(format!("Actual fingerprint: {}", current))
+            .with_status_code(422)
+            .with_status("Unprocessable Entity")
+            .build(),
+    )
+}
+
+/// Computes the expiry of a key recorded now
+fn expiry(ttl: Duration) -> Instant {
+    let now = Instant::now();
+    now.checked_add(ttl).unwrap_or_else(|| now + FAR_FUTURE)
+}
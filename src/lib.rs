@@ -1,4 +1,34 @@
+#[allow(clippy::result_large_err)]
+pub mod checksumx;
+#[allow(clippy::result_large_err)]
+pub mod compressx;
+pub mod concurrencyx;
+#[allow(clippy::result_large_err)]
+pub mod cookiex;
+#[allow(clippy::result_large_err)]
+pub mod corsx;
 pub mod errorsx;
+#[allow(clippy::result_large_err)]
+pub mod featureflagx;
+#[allow(clippy::result_large_err)]
+pub mod i18nx;
+#[allow(clippy::result_large_err)]
+pub mod idempotencyx;
+#[allow(clippy::result_large_err)]
+pub mod lockx;
+#[allow(clippy::result_large_err)]
+pub mod mailx;
+#[allow(clippy::result_large_err)]
+pub mod moneyx;
+#[allow(clippy::result_large_err)]
+pub mod pathx;
+pub mod poolx;
+#[allow(clippy::result_large_err)]
+pub mod queuex;
+pub mod requestidx;
+pub mod streamx;
 pub mod stringsx;
 pub mod tracex;
 pub mod uuidx;
+#[allow(clippy::result_large_err)]
+pub mod watchx;
@@ -0,0 +1,482 @@
+//! # Lockx
+//!
+//! This module provides advisory file locks for coordinating processes on one machine. Locks are
+//! taken with the operating system's file locking (`flock` on Unix, `LockFileEx` on Windows), so
+//! they are released automatically if the holding process exits or crashes. The holder of an
+//! exclusive lock records its process ID in the lock file, which is used to report who holds a
+//! lock and to detect lock files left behind by processes that died.
+//!
+//! On Unix, lock files are never opened through a symbolic link, and the process ID is only
+//! written to a file owned by the current user with a single link, so a lock path in a shared
+//! directory cannot be abused to truncate another file.
+//!
+//! ## Overview
+//!
+//! The main components are:
+//! - `FileLock`: A held lock, released when dropped
+//! - `LockMode`: Shared (many readers) or exclusive (one writer)
+//! - `LockStatus`: The state of a lock file as seen from outside
+//! - `single_instance`: Ensures only one instance of a program runs at a time
+//!
+//! ## Usage Scenarios
+//!
+//! Ideal for:
+//! - Preventing overlapping runs of cron jobs and CLI tools
+//! - Serializing writers of a shared file or directory while allowing concurrent readers
+//!
+//! Locks are advisory: they only exclude processes that also take the lock. Lock files are never
+//! deleted, since removing a file another process has already opened would let two holders
+//! coexist.
+//!
+//! ### Example
+//! ```rust,no_run
+//! use x::lockx;
+//!
+//! # fn run() -> Result<(), x::errorsx::Errorsx> {
+//! // Fails with status code 409 while another run holds the lock
+//! let _guard = lockx::single_instance("nightly-report")?;
+//!
+//! // ... do the work; the lock is released when `_guard` is dropped
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    fs::{File, OpenOptions, TryLockError},
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::errorsx::Errorsx;
+
+/// How a lock is shared with other holders
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LockMode {
+    /// Any number of shared holders, but no exclusive holder
+    Shared,
+    /// A single holder
+    Exclusive,
+}
+
+/// The state of a lock file as seen from outside
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockStatus {
+    /// Nobody holds the lock exclusively; shared holders are not detected, since that would
+    /// require taking the lock exclusively
+    Unlocked,
+    /// The lock is held; `pid` is the process ID recorded by an exclusive holder
+    Held { pid: Option<u32> },
+    /// Nobody holds the lock, but the file records the process ID of a holder that exited
+    /// without releasing it
+    Stale { pid: u32 },
+    /// Nobody holds the lock, but the file records the process ID of a holder that did not
+    /// release it, and whether that process is still running cannot be determined on this
+    /// platform
+    Unknown { pid: u32 },
+}
+
+/// A held advisory file lock
+///
+/// The lock is released when the FileLock is dropped or `release` is called.
+///
+/// # Fields
+/// * `file` - The open lock file
+/// * `path` - The path of the lock file
+/// * `mode` - The mode the lock is held in
+#[derive(Debug)]
+pub struct FileLock {
+    file: File,
+    path: PathBuf,
+    mode: LockMode,
+    released: bool,
+}
+
+impl FileLock {
+    /// Takes an exclusive lock without waiting
+    ///
+    /// The lock file is created if it does not exist.
+    ///
+    /// # Parameters
+    /// * `path` - The lock file
+    ///
+    /// # Returns
+    /// The FileLock, or an `Errorsx` with status code 409 if the lock is held elsewhere
+    #[track_caller]
+    pub fn try_acquire(path: impl AsRef<Path>) -> Result<Self, Errorsx> {
+        Self::open(path.as_ref(), LockMode::Exclusive, false)
+    }
+
+    /// Takes a shared lock without waiting
+    ///
+    /// # Parameters
+    /// * `path` - The lock file
+    ///
+    /// # Returns
+    /// The FileLock, or an `Errorsx` with status code 409 if an exclusive lock is held elsewhere
+    #[track_caller]
+    pub fn try_acquire_shared(path: impl AsRef<Path>) -> Result<Self, Errorsx> {
+        Self::open(path.as_ref(), LockMode::Shared, false)
+    }
+
+    /// Takes an exclusive lock, blocking the current thread until it is available
+    ///
+    /// From async code, call this through `tokio::task::spawn_blocking`.
+    ///
+    /// # Parameters
+    /// * `path` - The lock file
+    ///
+    /// # Returns
+    /// The FileLock, or an `Errorsx` if the lock file could not be opened or locked
+    #[track_caller]
+    pub fn acquire(path: impl AsRef<Path>) -> Result<Self, Errorsx> {
+        Self::open(path.as_ref(), LockMode::Exclusive, true)
+    }
+
+    /// Takes a shared lock, blocking the current thread until it is available
+    ///
+    /// # Parameters
+    /// * `path` - The lock file
+    ///
+    /// # Returns
+    /// The FileLock, or an `Errorsx` if the lock file could not be opened or locked
+    #[track_caller]
+    pub fn acquire_shared(path: impl AsRef<Path>) -> Result<Self, Errorsx> {
+        Self::open(path.as_ref(), LockMode::Shared, true)
+    }
+
+    /// Inspects a lock file without taking it exclusively
+    ///
+    /// The file is probed with a shared lock, which never makes a concurrent `try_acquire_shared`
+    /// fail; a `try_acquire` racing with the probe can still see the lock as held for that
+    /// moment. The result is a snapshot; the lock may change hands right after it is taken.
+    ///
+    /// # Parameters
+    /// * `path` - The lock file
+    ///
+    /// # Returns
+    /// The LockStatus (`Unlocked` if the file does not exist), or an `Errorsx` if the file could
+    /// not be inspected
+    #[track_caller]
+    pub fn status(path: impl AsRef<Path>) -> Result<LockStatus, Errorsx> {
+        let path = path.as_ref();
+        let mut options = OpenOptions::new();
+        options.read(true);
+        no_follow(&mut options);
+        let mut file = match options.open(path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(LockStatus::Unlocked)
+            }
+            Err(err) => return Err(open_error(path, err)),
+        };
+
+        match file.try_lock_shared() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                return Ok(LockStatus::Held {
+                    pid: read_pid(&mut file),
+                })
+            }
+            Err(TryLockError::Error(err)) => return Err(lock_error(path, err)),
+        }
+        // Without an exclusive holder any recorded PID was left behind by a previous holder
+        let pid = read_pid(&mut file);
+        let _ = file.unlock();
+
+        Ok(match pid {
+            None => LockStatus::Unlocked,
+            Some(pid) => match process_alive(pid) {
+                Some(false) => LockStatus::Stale { pid },
+                Some(true) => LockStatus::Unlocked,
+                None => LockStatus::Unknown { pid },
+            },
+        })
+    }
+
+    /// Gets the path of the lock file
+    ///
+    /// # Returns
+    /// The lock file path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Gets the mode the lock is held in
+    ///
+    /// # Returns
+    /// The LockMode
+    pub fn mode(&self) -> LockMode {
+        self.mode
+    }
+
+    /// Releases the lock, reporting failures that dropping would ignore
+    ///
+    /// # Returns
+    /// `Ok(())` once the lock is released, or an `Errorsx` if unlocking failed
+    #[track_caller]
+    pub fn release(mut self) -> Result<(), Errorsx> {
+        self.released = true;
+        if self.mode == LockMode::Exclusive {
+            // Clear the recorded PID first so the file is not reported as stale afterwards
+            let _ = self.file.set_len(0);
+        }
+        self.file.unlock().map_err(|err| {
+            Errorsx::builder("Failed to release file lock")
+                .with_context(format!("Path: {}", self.path.display()))
+                .with_source(err)
+                .build()
+        })
+    }
+
+    /// Opens the lock file and takes the lock
+    #[track_caller]
+    fn open(path: &Path, mode: LockMode, blocking: bool) -> Result<Self, Errorsx> {
+        let mut options = OpenOptions::new();
+        options.read(true).write(true).create(true).truncate(false);
+        no_follow(&mut options);
+        let mut file = options.open(path).map_err(|err| open_error(path, err))?;
+
+        let result = match (mode, blocking) {
+            (LockMode::Exclusive, false) => file.try_lock(),
+            (LockMode::Shared, false) => file.try_lock_shared(),
+            (LockMode::Exclusive, true) => file.lock().map_err(TryLockError::Error),
+            (LockMode::Shared, true) => file.lock_shared().map_err(TryLockError::Error),
+        };
+        match result {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                let mut builder = Errorsx::builder("File lock is held by another process")
+                    .with_context(format!("Path: {}", path.display()));
+                if let Some(pid) = read_pid(&mut file) {
+                    builder = builder.with_context(format!("Holder PID: {}", pid));
+                }
+                return Err(builder
+                    .with_status_code(409)
+                    .with_status("Conflict")
+                    .build());
+            }
+            Err(TryLockError::Error(err)) => return Err(lock_error(path, err)),
+        }
+
+        if mode == LockMode::Exclusive {
+            check_owned(&file, path)?;
+            if let Some(pid) = read_pid(&mut file) {
+                if pid != std::process::id() && process_alive(pid) == Some(false) {
+                    tracing::warn!(path = %path.display(), pid, "Recovered stale file lock");
+                }
+            }
+            write_pid(&mut file).map_err(|err| {
+                Errorsx::builder("Failed to record lock holder")
+                    .with_context(format!("Path: {}", path.display()))
+                    .with_source(err)
+                    .build()
+            })?;
+        }
+
+        Ok(Self {
+            file,
+            path: path.to_path_buf(),
+            mode,
+            released: false,
+        })
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        if self.released {
+            return;
+        }
+        if self.mode == LockMode::Exclusive {
+            let _ = self.file.set_len(0);
+        }
+        let _ = self.file.unlock();
+    }
+}
+
+/// Ensures only one instance of a program runs at a time
+///
+/// An exclusive lock is taken on `<name>.lock` in `$XDG_RUNTIME_DIR` if set. Otherwise a
+/// directory private to the current user is used: on Unix `x-locks-<uid>` in the system
+/// temporary directory, created with mode `0700` and rejected if another user owns it or can
+/// write to it; elsewhere the per-user temporary directory. Hold the returned lock for as long
+/// as the program runs.
+///
+/// # Parameters
+/// * `name` - The program name; letters, digits, `.`, `_` and `-` only
+///
+/// # Returns
+/// The held FileLock, or an `Errorsx` with status code 409 if another instance is running
+#[track_caller]
+pub fn single_instance(name: &str) -> Result<FileLock, Errorsx> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    if !valid {
+        return Err(Errorsx::builder("Invalid instance name")
+            .with_context(format!("Name: {}", name))
+            .build());
+    }
+
+    let dir = match std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_dir())
+    {
+        Some(dir) => dir,
+        None => private_lock_dir()?,
+    };
+    let path = dir.join(format!("{}.lock", name));
+
+    FileLock::try_acquire(&path).map_err(|err| {
+        if *err.status_code() != Some(409) {
+            return err;
+        }
+        Errorsx::builder("Another instance is already running")
+            .with_context(format!("Name: {}", name))
+            .with_source(err)
+            .with_status_code(409)
+            .with_status("Conflict")
+            .build()
+    })
+}
+
+/// Creates or validates the per-user lock directory in the shared temporary directory
+#[cfg(unix)]
+#[track_caller]
+fn private_lock_dir() -> Result<PathBuf, Errorsx> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+
+    // SAFETY: geteuid has no preconditions and cannot fail
+    let uid = unsafe { libc::geteuid() };
+    let dir = std::env::temp_dir().join(format!("x-locks-{}", uid));
+    match std::fs::DirBuilder::new().mode(0o700).create(&dir) {
+        Ok(()) => {}
+        Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {}
+        Err(err) => {
+            return Err(Errorsx::builder("Failed to create lock directory")
+                .with_context(format!("Path: {}", dir.display()))
+                .with_source(err)
+                .build())
+        }
+    }
+
+    let metadata = std::fs::symlink_metadata(&dir).map_err(|err| {
+        Errorsx::builder("Failed to inspect lock directory")
+            .with_context(format!("Path: {}", dir.display()))
+            .with_source(err)
+            .build()
+    })?;
+    if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o022 != 0 {
+        return Err(
+            Errorsx::builder("Lock directory is not private to the current user")
+                .with_context(format!("Path: {}", dir.display()))
+                .with_status_code(403)
+                .with_status("Forbidden")
+                .build(),
+        );
+    }
+    Ok(dir)
+}
+
+/// Gets the per-user temporary directory
+#[cfg(not(unix))]
+fn private_lock_dir() -> Result<PathBuf, Errorsx> {
+    Ok(std::env::temp_dir())
+}
+
+/// Refuses to open the final path component through a symbolic link
+#[cfg(unix)]
+fn no_follow(options: &mut OpenOptions) {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    options.custom_flags(libc::O_NOFOLLOW);
+}
+
+#[cfg(not(unix))]
+fn no_follow(_options: &mut OpenOptions) {}
+
+/// Checks that a lock file belongs to the current user before the holder PID is written to it
+///
+/// A file with several links could be another file of the user that was linked into a shared
+/// directory, so writing to it would truncate that file.
+#[cfg(unix)]
+#[track_caller]
+fn check_owned(file: &File, path: &Path) -> Result<(), Errorsx> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = file.metadata().map_err(|err| open_error(path, err))?;
+    // SAFETY: geteuid has no preconditions and cannot fail
+    let uid = unsafe { libc::geteuid() };
+    if metadata.uid() == uid && metadata.nlink() == 1 {
+        return Ok(());
+    }
+    Err(Errorsx::builder("Unsafe lock file")
+        .with_context("Lock files must be owned by the current user and have a single link")
+        .with_context(format!("Path: {}", path.display()))
+        .with_context(format!("Owner UID: {}", metadata.uid()))
+        .with_context(format!("Links: {}", metadata.nlink()))
+        .with_status_code(403)
+        .with_status("Forbidden")
+        .build())
+}
+
+#[cfg(not(unix))]
+fn check_owned(_file: &File, _path: &Path) -> Result<(), Errorsx> {
+    Ok(())
+}
+
+/// Reads the process ID recorded in a lock file
+fn read_pid(file: &mut File) -> Option<u32> {
+    let mut contents = String::new();
+    file.seek(SeekFrom::Start(0)).ok()?;
+    file.take(32).read_to_string(&mut contents).ok()?;
+    contents.trim().parse().ok()
+}
+
+/// Records the current process ID in a lock file
+fn write_pid(file: &mut File) -> std::io::Result<()> {
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    writeln!(file, "{}", std::process::id())?;
+    file.flush()
+}
+
+/// Checks whether a process is running, if the platform allows finding out without extra
+/// dependencies
+#[cfg(unix)]
+fn process_alive(pid: u32) -> Option<bool> {
+    let pid = libc::pid_t::try_from(pid).ok().filter(|pid| *pid > 0)?;
+    // SAFETY: signal 0 performs the existence and permission checks without sending anything
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return Some(true);
+    }
+    match std::io::Error::last_os_error().raw_os_error() {
+        Some(libc::ESRCH) => Some(false),
+        // The process exists but belongs to another user
+        Some(libc::EPERM) => Some(true),
+        _ => None,
+    }
+}
+
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> Option<bool> {
+    None
+}
+
+#[track_caller]
+fn open_error(path: &Path, err: std::io::Error) -> Errorsx {
+    Errorsx::builder("Failed to open lock file")
+        .with_context(format!("Path: {}", path.display()))
+        .with_source(err)
+        .build()
+}
+
+#[track_caller]
+fn lock_error(path: &Path, err: std::io::Error) -> Errorsx {
+    Errorsx::builder("Failed to lock file")
+        .with_context(format!("Path: {}", path.display()))
+        .with_source(err)
+        .build()
+}
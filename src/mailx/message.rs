@@ -0,0 +1,586 @@
+//! Email addresses and messages
+//!
+//! This module provides `Address` with validation, `Attachment`, and the `Message` type with its
+//! builder. Addresses, subjects and attachment metadata are checked when they are set or when the
+//! message is built, so an invalid message never reaches a transport. Control characters are
+//! rejected everywhere they could end up in a header, which rules out header injection.
+
+use std::{fmt::Display, str::FromStr};
+
+use crate::errorsx::Errorsx;
+
+/// Longest address accepted, per RFC 5321
+const MAX_ADDRESS_LEN: usize = 254;
+
+/// Longest local part (before the `@`) accepted, per RFC 5321
+const MAX_LOCAL_LEN: usize = 64;
+
+/// Longest domain label accepted
+const MAX_LABEL_LEN: usize = 63;
+
+/// A validated email address with an optional display name
+///
+/// Only ASCII addresses are accepted. The domain must contain at least one dot, which catches
+/// typos like `user@example` at the cost of rejecting single-label hosts.
+///
+/// # Fields
+/// * `name` - The display name, e.g. `Ada Lovelace`
+/// * `email` - The bare address, e.g. `ada@example.com`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Address {
+    name: Option<String>,
+    email: String,
+}
+
+impl Address {
+    /// Creates a new Address from its parts
+    ///
+    /// # Parameters
+    /// * `name` - The display name, if any
+    /// * `email` - The bare address
+    ///
+    /// # Returns
+    /// The Address, or an `Errorsx` if the address or the name is invalid
+    #[track_caller]
+    pub fn new(name: Option<&str>, email: &str) -> Result<Self, Errorsx> {
+        let email = email.trim();
+        if !is_valid_email(email) {
+            return Err(Errorsx::builder("Invalid email address")
+                .with_context(format!("Address: {}", email))
+                .build());
+        }
+        let name = name.map(str::trim).filter(|name| !name.is_empty());
+        if let Some(name) = name {
+            if name.chars().any(char::is_control) {
+                return Err(Errorsx::builder("Invalid email display name")
+                    .with_context(format!("Name: {:?}", name))
+                    .build());
+            }
+        }
+        Ok(Self {
+            name: name.map(str::to_string),
+            email: email.to_string(),
+        })
+    }
+
+    /// Parses an address written as `ada@example.com` or `Ada Lovelace <ada@example.com>`
+    ///
+    /// A quoted display name (`"Lovelace, Ada" <ada@example.com>`) is unquoted.
+    ///
+    /// # Parameters
+    /// * `value` - The address to parse
+    ///
+    /// # Returns
+    /// The Address, or an `Errorsx` if the value is not a valid address
+    #[track_caller]
+    pub fn parse(value: &str) -> Result<Self, Errorsx> {
+        let value = value.trim();
+        let Some(without_close) = value.strip_suffix('>') else {
+            return Self::new(None, value);
+        };
+        let Some(open) = without_close.rfind('<') else {
+            return Err(Errorsx::builder("Invalid email address")
+                .with_context(format!("Address: {}", value))
+                .build());
+        };
+
+        let name = without_close[..open].trim();
+        let name = match name
+            .strip_prefix('"')
+            .and_then(|quoted| quoted.strip_suffix('"'))
+        {
+            Some(quoted) => quoted.replace("\\\"", "\"").replace("\\\\", "\\"),
+            None => name.to_string(),
+        };
+        Self::new(Some(&name), &without_close[open + 1..])
+    }
+
+    /// Gets the display name
+    ///
+    /// # Returns
+    /// The display name, if any
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Gets the bare address
+    ///
+    /// # Returns
+    /// The address without the display name
+    pub fn email(&self) -> &str {
+        &self.email
+    }
+
+    /// Gets the domain part of the address
+    ///
+    /// # Returns
+    /// The part after the `@`
+    pub fn domain(&self) -> &str {
+        self.email.rsplit_once('@').map_or("", |(_, domain)| domain)
+    }
+}
+
+impl Display for Address {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.name {
+            None => f.write_str(&self.email),
+            Some(name) if name.chars().any(|c| "()<>[]:;@\\,.\"".contains(c)) => {
+                let escaped = name.replace('\\', "\\\\").replace('"', "\\\"");
+                write!(f, "\"{}\" <{}>", escaped, self.email)
+            }
+            Some(name) => write!(f, "{} <{}>", name, self.email),
+        }
+    }
+}
+
+impl FromStr for Address {
+    type Err = Errorsx;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::parse(value)
+    }
+}
+
+/// A file attached to a message
+///
+/// # Fields
+/// * `filename` - The file name shown to the recipient
+/// * `content_type` - The MIME type, e.g. `application/pdf`
+/// * `data` - The file contents
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    filename: String,
+    content_type: String,
+    data: Vec<u8>,
+}
+
+impl Attachment {
+    /// Creates a new Attachment
+    ///
+    /// The file name and content type are validated when the message is built.
+    ///
+    /// # Parameters
+    /// * `filename` - The file name shown to the recipient
+    /// * `content_type` - The MIME type, e.g. `text/csv`
+    /// * `data` - The file contents
+    ///
+    /// # Returns
+    /// A new Attachment
+    pub fn new(
+        filename: impl Into<String>,
+        content_type: impl Into<String>,
+        data: impl Into<Vec<u8>>,
+    ) -> Self {
+        Self {
+            filename: filename.into(),
+            content_type: content_type.into(),
+            data: data.into(),
+        }
+    }
+
+    /// Gets the file name
+    ///
+    /// # Returns
+    /// The file name
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    /// Gets the MIME type
+    ///
+    /// # Returns
+    /// The content type
+    pub fn content_type(&self) -> &str {
+        &self.content_type
+    }
+
+    /// Gets the file contents
+    ///
+    /// # Returns
+    /// The attachment data
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// Builder for creating Message instances
+///
+/// Address setters validate their input immediately; the first invalid value is reported by
+/// `build`.
+#[derive(Debug, Default)]
+pub struct MessageBuilder {
+    from: Option<Address>,
+    reply_to: Vec<Address>,
+    to: Vec<Address>,
+    cc: Vec<Address>,
+    bcc: Vec<Address>,
+    subject: String,
+    text: Option<String>,
+    html: Option<String>,
+    attachments: Vec<Attachment>,
+    error: Option<Errorsx>,
+}
+
+impl MessageBuilder {
+    /// Creates a new MessageBuilder
+    ///
+    /// # Returns
+    /// A new MessageBuilder with no sender, recipients or body
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the sender
+    ///
+    /// # Parameters
+    /// * `address` - The sender, e.g. `Example <noreply@example.com>`
+    ///
+    /// # Returns
+    /// The MessageBuilder instance
+    #[track_caller]
+    pub fn with_from(mut self, address: impl AsRef<str>) -> Self {
+        if let Some(address) = self.parse_address(address.as_ref()) {
+            self.from = Some(address);
+        }
+        self
+    }
+
+    /// Adds a `Reply-To` address
+    ///
+    /// # Parameters
+    /// * `address` - The address replies should go to
+    ///
+    /// # Returns
+    /// The MessageBuilder instance
+    #[track_caller]
+    pub fn with_reply_to(mut self, address: impl AsRef<str>) -> Self {
+        if let Some(address) = self.parse_address(address.as_ref()) {
+            self.reply_to.push(address);
+        }
+        self
+    }
+
+    /// Adds a `To` recipient
+    ///
+    /// # Parameters
+    /// * `address` - The recipient
+    ///
+    /// # Returns
+    /// The MessageBuilder instance
+    #[track_caller]
+    pub fn with_to(mut self, address: impl AsRef<str>) -> Self {
+        if let Some(address) = self.parse_address(address.as_ref()) {
+            self.to.push(address);
+        }
+        self
+    }
+
+    /// Adds a `Cc` recipient
+    ///
+    /// # Parameters
+    /// * `address` - The recipient
+    ///
+    /// # Returns
+    /// The MessageBuilder instance
+    #[track_caller]
+    pub fn with_cc(mut self, address: impl AsRef<str>) -> Self {
+        if let Some(address) = self.parse_address(address.as_ref()) {
+            self.cc.push(address);
+        }
+        self
+    }
+
+    /// Adds a `Bcc` recipient
+    ///
+    /// Bcc recipients receive the message but are not listed in its headers.
+    ///
+    /// # Parameters
+    /// * `address` - The recipient
+    ///
+    /// # Returns
+    /// The MessageBuilder instance
+    #[track_caller]
+    pub fn with_bcc(mut self, address: impl AsRef<str>) -> Self {
+        if let Some(address) = self.parse_address(address.as_ref()) {
+            self.bcc.push(address);
+        }
+        self
+    }
+
+    /// Sets the subject
+    ///
+    /// # Parameters
+    /// * `subject` - The subject line; line breaks are rejected by `build`
+    ///
+    /// # Returns
+    /// The MessageBuilder instance
+    pub fn with_subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = subject.into();
+        self
+    }
+
+    /// Sets the plain text body
+    ///
+    /// # Parameters
+    /// * `text` - The plain text body
+    ///
+    /// # Returns
+    /// The MessageBuilder instance
+    pub fn with_text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// Sets the HTML body
+    ///
+    /// When a plain text body is set as well, both are sent as alternatives and the client
+    /// picks one.
+    ///
+    /// # Parameters
+    /// * `html` - The HTML body
+    ///
+    /// # Returns
+    /// The MessageBuilder instance
+    pub fn with_html(mut self, html: impl Into<String>) -> Self {
+        self.html = Some(html.into());
+        self
+    }
+
+    /// Adds an attachment
+    ///
+    /// # Parameters
+    /// * `attachment` - The attachment
+    ///
+    /// # Returns
+    /// The MessageBuilder instance
+    pub fn with_attachment(mut self, attachment: Attachment) -> Self {
+        self.attachments.push(attachment);
+        self
+    }
+
+    /// Builds the Message
+    ///
+    /// # Returns
+    /// The Message, or an `Errorsx` if an address was invalid, the sender, recipients or body
+    /// are missing, the subject contains control characters or an attachment is malformed
+    #[track_caller]
+    pub fn build(self) -> Result<Message, Errorsx> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        let Some(from) = self.from else {
+            return Err(Errorsx::builder("Message has no sender").build());
+        };
+        if self.to.is_empty() && self.cc.is_empty() && self.bcc.is_empty() {
+            return Err(Errorsx::builder("Message has no recipients").build());
+        }
+        if self.text.is_none() && self.html.is_none() {
+            return Err(Errorsx::builder("Message has no body").build());
+        }
+        if self.subject.chars().any(char::is_control) {
+            return Err(Errorsx::builder("Invalid message subject")
+                .with_context(format!("Subject: {:?}", self.subject))
+                .build());
+        }
+        for attachment in &self.attachments {
+            validate_attachment(attachment)?;
+        }
+
+        Ok(Message {
+            from,
+            reply_to: self.reply_to,
+            to: self.to,
+            cc: self.cc,
+            bcc: self.bcc,
+            subject: self.subject,
+            text: self.text,
+            html: self.html,
+            attachments: self.attachments,
+        })
+    }
+
+    /// Parses an address, keeping the first failure for `build`
+    #[track_caller]
+    fn parse_address(&mut self, value: &str) -> Option<Address> {
+        match Address::parse(value) {
+            Ok(address) => Some(address),
+            Err(err) => {
+                self.error.get_or_insert(err);
+                None
+            }
+        }
+    }
+}
+
+/// A validated email message
+///
+/// # Fields
+/// * `from` - The sender
+/// * `reply_to` - Addresses replies should go to
+/// * `to`, `cc`, `bcc` - The recipients
+/// * `subject` - The subject line
+/// * `text` - The plain text body
+/// * `html` - The HTML body
+/// * `attachments` - Attached files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    from: Address,
+    reply_to: Vec<Address>,
+    to: Vec<Address>,
+    cc: Vec<Address>,
+    bcc: Vec<Address>,
+    subject: String,
+    text: Option<String>,
+    html: Option<String>,
+    attachments: Vec<Attachment>,
+}
+
+impl Message {
+    /// Creates a new MessageBuilder
+    ///
+    /// # Returns
+    /// A new MessageBuilder
+    pub fn builder() -> MessageBuilder {
+        MessageBuilder::new()
+    }
+
+    /// Gets the sender
+    ///
+    /// # Returns
+    /// The sender Address
+    pub fn from(&self) -> &Address {
+        &self.from
+    }
+
+    /// Gets the `Reply-To` addresses
+    ///
+    /// # Returns
+    /// The Reply-To addresses
+    pub fn reply_to(&self) -> &[Address] {
+        &self.reply_to
+    }
+
+    /// Gets the `To` recipients
+    ///
+    /// # Returns
+    /// The To addresses
+    pub fn to(&self) -> &[Address] {
+        &self.to
+    }
+
+    /// Gets the `Cc` recipients
+    ///
+    /// # Returns
+    /// The Cc addresses
+    pub fn cc(&self) -> &[Address] {
+        &self.cc
+    }
+
+    /// Gets the `Bcc` recipients
+    ///
+    /// # Returns
+    /// The Bcc addresses
+    pub fn bcc(&self) -> &[Address] {
+        &self.bcc
+    }
+
+    /// Gets every recipient of the message
+    ///
+    /// # Returns
+    /// An iterator over the To, Cc and Bcc addresses, in that order
+    pub fn recipients(&self) -> impl Iterator<Item = &Address> {
+        self.to.iter().chain(&self.cc).chain(&self.bcc)
+    }
+
+    /// Gets the subject
+    ///
+    /// # Returns
+    /// The subject line
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// Gets the plain text body
+    ///
+    /// # Returns
+    /// The plain text body, if set
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+
+    /// Gets the HTML body
+    ///
+    /// # Returns
+    /// The HTML body, if set
+    pub fn html(&self) -> Option<&str> {
+        self.html.as_deref()
+    }
+
+    /// Gets the attachments
+    ///
+    /// # Returns
+    /// The attached files
+    pub fn attachments(&self) -> &[Attachment] {
+        &self.attachments
+    }
+}
+
+/// Checks a bare address
+fn is_valid_email(email: &str) -> bool {
+    if email.len() > MAX_ADDRESS_LEN || !email.is_ascii() {
+        return false;
+    }
+    let Some((local, domain)) = email.rsplit_once('@') else {
+        return false;
+    };
+
+    let local_valid = !local.is_empty()
+        && local.len() <= MAX_LOCAL_LEN
+        && local.split('.').all(|atom| {
+            !atom.is_empty()
+                && atom
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~".contains(c))
+        });
+
+    let labels: Vec<&str> = domain.split('.').collect();
+    let domain_valid = labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && label.len() <= MAX_LABEL_LEN
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        });
+
+    local_valid && domain_valid
+}
+
+/// Checks an attachment's file name and content type
+#[track_caller]
+fn validate_attachment(attachment: &Attachment) -> Result<(), Errorsx> {
+    let filename = &attachment.filename;
+    if filename.trim().is_empty() || filename.chars().any(char::is_control) {
+        return Err(Errorsx::builder("Invalid attachment file name")
+            .with_context(format!("Filename: {:?}", filename))
+            .build());
+    }
+
+    let is_token = |value: &str| {
+        !value.is_empty()
+            && value
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "!#$&-^_.+".contains(c))
+    };
+    let valid_type = attachment
+        .content_type
+        .split_once('/')
+        .is_some_and(|(kind, subtype)| is_token(kind) && is_token(subtype));
+    if !valid_type {
+        return Err(Errorsx::builder("Invalid attachment content type")
+            .with_context(format!("Filename: {}", filename))
+            .with_context(format!("Content-Type: {}", attachment.content_type))
+            .build());
+    }
+    Ok(())
+}
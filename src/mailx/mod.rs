@@ -0,0 +1,163 @@
+//! # Mailx
+//!
+//! This module provides a consistent way to build and send transactional email. Messages are
+//! assembled with a typed builder that validates addresses and header values up front, and are
+//! sent through the `Mailer` trait so application code does not depend on a particular
+//! transport. `TestMailer` captures sent messages in memory for assertions in tests.
+//!
+//! ## Overview
+//!
+//! The main components are:
+//! - `Address`: A validated email address with an optional display name
+//! - `Message` / `MessageBuilder`: Sender, To/Cc/Bcc recipients, subject, text and HTML
+//!   alternatives and attachments
+//! - `Attachment`: A file attached to a message
+//! - `Mailer`: Trait for transports
+//! - `TestMailer`: A transport recording messages instead of sending them
+//! - `smtp::SmtpMailer`: An SMTP transport with TLS and connection pooling (requires the `smtp`
+//!   feature)
+//!
+//! ## Usage Scenarios
+//!
+//! Ideal for:
+//! - Sign-up confirmations, password resets and receipts
+//! - Asserting on sent email in tests without an SMTP server
+//!
+//! ### Example
+//! ```rust
+//! use x::mailx::{Attachment, Mailer, Message, TestMailer};
+//!
+//! # fn run() -> Result<(), x::errorsx::Errorsx> {
+//! let message = Message::builder()
+//!     .with_from("Example <noreply@example.com>")
+//!     .with_to("ada@example.com")
+//!     .with_bcc("audit@example.com")
+//!     .with_subject("Your receipt")
+//!     .with_text("Thanks for your order.")
+//!     .with_html("<p>Thanks for your order.</p>")
+//!     .with_attachment(Attachment::new("receipt.csv", "text/csv", "item,total\n"))
+//!     .build()?;
+//!
+//! let mailer = TestMailer::new();
+//! futures::executor::block_on(mailer.send(&message))?;
+//! assert_eq!(mailer.messages()[0].subject(), "Your receipt");
+//! # Ok(())
+//! # }
+//! # run().unwrap();
+//! ```
+pub mod message;
+#[cfg(feature = "smtp")]
+pub mod smtp;
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use crate::errorsx::Errorsx;
+
+pub use message::{Address, Attachment, Message, MessageBuilder};
+
+/// A transport that delivers messages
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    /// Sends a message to all of its recipients
+    ///
+    /// # Parameters
+    /// * `message` - The message to send
+    ///
+    /// # Returns
+    /// `Ok(())` once the transport accepted the message, or an `Errorsx` if delivery failed
+    async fn send(&self, message: &Message) -> Result<(), Errorsx>;
+}
+
+/// A Mailer that records messages instead of sending them
+///
+/// Clones share the same record, so a test can keep one handle while the code under test owns
+/// another.
+#[derive(Debug, Clone, Default)]
+pub struct TestMailer {
+    sent: Arc<Mutex<Vec<Message>>>,
+}
+
+impl TestMailer {
+    /// Creates a new TestMailer
+    ///
+    /// # Returns
+    /// A TestMailer with no recorded messages
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the recorded messages
+    ///
+    /// # Returns
+    /// Copies of all messages sent so far, oldest first
+    pub fn messages(&self) -> Vec<Message> {
+        self.sent.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Gets the most recently sent message
+    ///
+    /// # Returns
+    /// A copy of the last message, or `None` if nothing was sent
+    pub fn last(&self) -> Option<Message> {
+        self.sent
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .last()
+            .cloned()
+    }
+
+    /// Gets the messages sent to an address
+    ///
+    /// # Parameters
+    /// * `email` - The bare recipient address, compared case-insensitively
+    ///
+    /// # Returns
+    /// Copies of the messages listing the address as a To, Cc or Bcc recipient
+    pub fn messages_to(&self, email: &str) -> Vec<Message> {
+        self.sent
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|message| {
+                message
+                    .recipients()
+                    .any(|address| address.email().eq_ignore_ascii_case(email))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Gets the number of recorded messages
+    ///
+    /// # Returns
+    /// The number of messages sent so far
+    pub fn len(&self) -> usize {
+        self.sent.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Checks whether no messages were sent
+    ///
+    /// # Returns
+    /// `true` if nothing was recorded
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forgets all recorded messages
+    pub fn clear(&self) {
+        self.sent.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+#[async_trait]
+impl Mailer for TestMailer {
+    async fn send(&self, message: &Message) -> Result<(), Errorsx> {
+        self.sent
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(message.clone());
+        Ok(())
+    }
+}
@@ -0,0 +1,296 @@
+//! SMTP transport
+//!
+//! This module provides `SmtpMailer`, available with the `smtp` feature. It delivers messages
+//! through an SMTP relay using pooled connections, with implicit TLS, STARTTLS or (for local
+//! development relays only) plaintext.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use lettre::{
+    message::{
+        header::ContentType, Attachment as LettreAttachment, Mailbox, MultiPart, SinglePart,
+    },
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Tokio1Executor,
+};
+
+use super::{Address, Mailer, Message};
+use crate::errorsx::Errorsx;
+
+/// How the connection to the relay is secured
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Security {
+    /// TLS from the first byte, usually on port 465
+    ImplicitTls,
+    /// A plaintext connection upgraded with STARTTLS, usually on port 587; the upgrade is required
+    #[default]
+    StartTls,
+    /// No encryption, usually on port 25; only suitable for local relays such as test inboxes
+    Plaintext,
+}
+
+/// Builder for creating SmtpMailer instances
+#[derive(Debug)]
+pub struct SmtpMailerBuilder {
+    host: String,
+    port: Option<u16>,
+    security: Security,
+    credentials: Option<(String, String)>,
+    timeout: Option<Duration>,
+}
+
+impl SmtpMailerBuilder {
+    /// Creates a new SmtpMailerBuilder
+    ///
+    /// # Parameters
+    /// * `host` - The relay host name
+    ///
+    /// # Returns
+    /// A new SmtpMailerBuilder using STARTTLS on the default port without authentication
+    pub fn new(host: impl Into<String>) -> Self {
+        Self {
+            host: host.into(),
+            port: None,
+            security: Security::default(),
+            credentials: None,
+            timeout: None,
+        }
+    }
+
+    /// Sets the port
+    ///
+    /// # Parameters
+    /// * `port` - The relay port; defaults to the standard port of the security mode
+    ///
+    /// # Returns
+    /// The SmtpMailerBuilder instance
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Sets how the connection is secured
+    ///
+    /// # Parameters
+    /// * `security` - The Security mode
+    ///
+    /// # Returns
+    /// The SmtpMailerBuilder instance
+    pub fn with_security(mut self, security: Security) -> Self {
+        self.security = security;
+        self
+    }
+
+    /// Sets the credentials used to authenticate with the relay
+    ///
+    /// # Parameters
+    /// * `username` - The user name
+    /// * `password` - The password
+    ///
+    /// # Returns
+    /// The SmtpMailerBuilder instance
+    pub fn with_credentials(
+        mut self,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        self.credentials = Some((username.into(), password.into()));
+        self
+    }
+
+    /// Sets the timeout for each SMTP command
+    ///
+    /// # Parameters
+    /// * `timeout` - The timeout
+    ///
+    /// # Returns
+    /// The SmtpMailerBuilder instance
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Builds the SmtpMailer
+    ///
+    /// No connection is opened until the first message is sent.
+    ///
+    /// # Returns
+    /// The SmtpMailer, or an `Errorsx` if the TLS configuration for the host could not be created
+    #[track_caller]
+    pub fn build(self) -> Result<SmtpMailer, Errorsx> {
+        let mut builder = match self.security {
+            Security::ImplicitTls => AsyncSmtpTransport::<Tokio1Executor>::relay(&self.host),
+            Security::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&self.host),
+            Security::Plaintext => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+                &self.host,
+            )),
+        }
+        .map_err(|err| {
+            Errorsx::builder("Failed to configure SMTP transport")
+                .with_context(format!("Host: {}", self.host))
+                .with_source(err)
+                .build()
+        })?;
+
+        if let Some(port) = self.port {
+            builder = builder.port(port);
+        }
+        if let Some((username, password)) = self.credentials {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+        if self.timeout.is_some() {
+            builder = builder.timeout(self.timeout);
+        }
+
+        Ok(SmtpMailer {
+            transport: builder.build(),
+            host: self.host,
+        })
+    }
+}
+
+/// A Mailer delivering messages through an SMTP relay
+///
+/// Connections are pooled and reused across sends; clones share the pool.
+#[derive(Clone)]
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    host: String,
+}
+
+impl SmtpMailer {
+    /// Creates a new SmtpMailerBuilder
+    ///
+    /// # Parameters
+    /// * `host` - The relay host name
+    ///
+    /// # Returns
+    /// A new SmtpMailerBuilder
+    pub fn builder(host: impl Into<String>) -> SmtpMailerBuilder {
+        SmtpMailerBuilder::new(host)
+    }
+
+    /// Checks that the relay is reachable and accepts the configured credentials
+    ///
+    /// # Returns
+    /// `Ok(())` if a connection could be established, otherwise an `Errorsx`
+    pub async fn test_connection(&self) -> Result<(), Errorsx> {
+        match self.transport.test_connection().await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(Errorsx::builder("SMTP relay is not reachable")
+                .with_context(format!("Host: {}", self.host))
+                .build()),
+            Err(err) => Err(Errorsx::builder("SMTP relay is not reachable")
+                .with_context(format!("Host: {}", self.host))
+                .with_source(err)
+                .build()),
+        }
+    }
+}
+
+impl std::fmt::Debug for SmtpMailer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SmtpMailer")
+            .field("host", &self.host)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, message: &Message) -> Result<(), Errorsx> {
+        let email = to_lettre(message)?;
+        self.transport.send(email).await.map_err(|err| {
+            Errorsx::builder("Failed to send email")
+                .with_context(format!("Host: {}", self.host))
+                .with_context(format!("Subject: {}", message.subject()))
+                .with_context(format!(
+                    "Transient: {}",
+                    err.is_transient() || err.is_timeout()
+                ))
+                .with_source(err)
+                .build()
+        })?;
+        Ok(())
+    }
+}
+
+/// Converts a validated Message into its MIME representation
+fn to_lettre(message: &Message) -> Result<lettre::Message, Errorsx> {
+    let mut builder = lettre::Message::builder()
+        .from(to_mailbox(message.from())?)
+        .subject(message.subject());
+    for address in message.reply_to() {
+        builder = builder.reply_to(to_mailbox(address)?);
+    }
+    for address in message.to() {
+        builder = builder.to(to_mailbox(address)?);
+    }
+    for address in message.cc() {
+        builder = builder.cc(to_mailbox(address)?);
+    }
+    for address in message.bcc() {
+        builder = builder.bcc(to_mailbox(address)?);
+    }
+
+    let body = match (message.text(), message.html()) {
+        (Some(text), Some(html)) => Body::Alternative(MultiPart::alternative_plain_html(
+            text.to_string(),
+            html.to_string(),
+        )),
+        (Some(text), None) => Body::Single(SinglePart::plain(text.to_string())),
+        (None, Some(html)) => Body::Single(SinglePart::html(html.to_string())),
+        (None, None) => Body::Single(SinglePart::plain(String::new())),
+    };
+
+    let result = if message.attachments().is_empty() {
+        match body {
+            Body::Single(part) => builder.singlepart(part),
+            Body::Alternative(part) => builder.multipart(part),
+        }
+    } else {
+        let mut mixed = match body {
+            Body::Single(part) => MultiPart::mixed().singlepart(part),
+            Body::Alternative(part) => MultiPart::mixed().multipart(part),
+        };
+        for attachment in message.attachments() {
+            let content_type = ContentType::parse(attachment.content_type()).map_err(|err| {
+                Errorsx::builder("Invalid attachment content type")
+                    .with_context(format!("Filename: {}", attachment.filename()))
+                    .with_context(format!("Content-Type: {}", attachment.content_type()))
+                    .with_source(err)
+                    .build()
+            })?;
+            mixed = mixed.singlepart(
+                LettreAttachment::new(attachment.filename().to_string())
+                    .body(attachment.data().to_vec(), content_type),
+            );
+        }
+        builder.multipart(mixed)
+    };
+
+    result.map_err(|err| {
+        Errorsx::builder("Failed to assemble email")
+            .with_context(format!("Subject: {}", message.subject()))
+            .with_source(err)
+            .build()
+    })
+}
+
+/// The body of a message before attachments are added
+enum Body {
+    Single(SinglePart),
+    Alternative(MultiPart),
+}
+
+/// Converts a validated Address into a mailbox
+fn to_mailbox(address: &Address) -> Result<Mailbox, Errorsx> {
+    let email = address.email().parse().map_err(|err| {
+        Errorsx::builder("Invalid email address")
+            .with_context(format!("Address: {}", address.email()))
+            .with_source(err)
+            .build()
+    })?;
+    Ok(Mailbox::new(address.name().map(str::to_string), email))
+}
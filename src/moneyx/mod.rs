@@ -0,0 +1,538 @@
+//! # Moneyx
+//!
+//! This module provides an exact currency amount type. Amounts are stored as an integer number
+//! of minor units (cents for USD, yen for JPY) together with their currency, so no value is ever
+//! rounded implicitly. All arithmetic is checked: overflow and mixing currencies are reported as
+//! `Errorsx` instead of producing a wrong amount.
+//!
+//! ## Overview
+//!
+//! The main components are:
+//! - `Currency`: An ISO 4217 code with the number of minor-unit digits
+//! - `Money`: An amount of minor units in a currency
+//!
+//! ## Allocation
+//!
+//! `Money::allocate` splits an amount by ratios and `Money::split` into equal parts. Both use the
+//! largest remainder method, so the parts always add up to the original amount exactly: the
+//! minor units that cannot be divided evenly are handed out one by one.
+//!
+//! ## Serialization
+//!
+//! `Money` serializes as `{"amount": 1050, "currency": "EUR"}` with the amount in minor units.
+//! Deserialization resolves the currency code against the built-in ISO 4217 table.
+//!
+//! ### Example
+//! ```rust
+//! use x::moneyx::{Currency, Money};
+//!
+//! # fn run() -> Result<(), x::errorsx::Errorsx> {
+//! let usd = Currency::from_code("USD")?;
+//! let total = Money::parse("100.00", usd)?;
+//!
+//! let parts = total.split(3)?;
+//! let formatted: Vec<String> = parts.iter().map(|p| p.to_string()).collect();
+//! assert_eq!(formatted, ["33.34 USD", "33.33 USD", "33.33 USD"]);
+//!
+//! let with_fee = total.checked_add(&Money::from_minor(250, usd))?;
+//! assert_eq!(with_fee.to_string(), "102.50 USD");
+//! # Ok(())
+//! # }
+//! # run().unwrap();
+//! ```
+
+use std::{cmp::Ordering, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::errorsx::Errorsx;
+
+/// ISO 4217 currencies known to `Currency::from_code`, with their minor-unit digits
+const KNOWN_CURRENCIES: &[(&str, u8)] = &[
+    ("AED", 2),
+    ("ARS", 2),
+    ("AUD", 2),
+    ("BHD", 3),
+    ("BRL", 2),
+    ("CAD", 2),
+    ("CHF", 2),
+    ("CLP", 0),
+    ("CNY", 2),
+    ("COP", 2),
+    ("CZK", 2),
+    ("DKK", 2),
+    ("EGP", 2),
+    ("EUR", 2),
+    ("GBP", 2),
+    ("HKD", 2),
+    ("HUF", 2),
+    ("IDR", 2),
+    ("ILS", 2),
+    ("INR", 2),
+    ("ISK", 0),
+    ("JOD", 3),
+    ("JPY", 0),
+    ("KRW", 0),
+    ("KWD", 3),
+    ("MXN", 2),
+    ("MYR", 2),
+    ("NGN", 2),
+    ("NOK", 2),
+    ("NZD", 2),
+    ("OMR", 3),
+    ("PHP", 2),
+    ("PKR", 2),
+    ("PLN", 2),
+    ("QAR", 2),
+    ("RON", 2),
+    ("SAR", 2),
+    ("SEK", 2),
+    ("SGD", 2),
+    ("THB", 2),
+    ("TND", 3),
+    ("TRY", 2),
+    ("TWD", 2),
+    ("UAH", 2),
+    ("USD", 2),
+    ("VND", 0),
+    ("ZAR", 2),
+];
+
+/// Largest supported number of minor-unit digits
+const MAX_EXPONENT: u8 = 18;
+
+/// A currency identified by its ISO 4217 code
+///
+/// # Fields
+/// * `code` - The three-letter uppercase code
+/// * `exponent` - The number of minor-unit digits, e.g. 2 for USD and 0 for JPY
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Currency {
+    code: [u8; 3],
+    exponent: u8,
+}
+
+impl Currency {
+    /// Looks up a currency in the built-in ISO 4217 table
+    ///
+    /// # Parameters
+    /// * `code` - The three-letter code, case-insensitive
+    ///
+    /// # Returns
+    /// The Currency, or an `Errorsx` if the code is unknown
+    #[track_caller]
+    pub fn from_code(code: &str) -> Result<Self, Errorsx> {
+        let upper = code.trim().to_ascii_uppercase();
+        KNOWN_CURRENCIES
+            .iter()
+            .find(|(known, _)| *known == upper)
+            .map(|(known, exponent)| Self {
+                code: code_bytes(known),
+                exponent: *exponent,
+            })
+            .ok_or_else(|| {
+                Errorsx::builder("Unknown currency code")
+                    .with_context(format!("Code: {}", code))
+                    .build()
+            })
+    }
+
+    /// Defines a currency that is not in the built-in table
+    ///
+    /// Such currencies work for arithmetic and formatting but cannot be deserialized.
+    ///
+    /// # Parameters
+    /// * `code` - A three-letter ASCII code, case-insensitive
+    /// * `exponent` - The number of minor-unit digits, at most 18
+    ///
+    /// # Returns
+    /// The Currency, or an `Errorsx` if the code or exponent is invalid
+    #[track_caller]
+    pub fn new(code: &str, exponent: u8) -> Result<Self, Errorsx> {
+        let upper = code.trim().to_ascii_uppercase();
+        if upper.len() != 3 || !upper.bytes().all(|b| b.is_ascii_uppercase()) {
+            return Err(Errorsx::builder("Invalid currency code")
+                .with_context(format!("Code: {}", code))
+                .with_context("Expected three ASCII letters")
+                .build());
+        }
+        if exponent > MAX_EXPONENT {
+            return Err(Errorsx::builder("Invalid currency exponent")
+                .with_context(format!("Code: {}", upper))
+                .with_context(format!("Exponent {} exceeds {}", exponent, MAX_EXPONENT))
+                .build());
+        }
+        Ok(Self {
+            code: code_bytes(&upper),
+            exponent,
+        })
+    }
+
+    /// Gets the currency code
+    ///
+    /// # Returns
+    /// The three-letter uppercase code
+    pub fn code(&self) -> &str {
+        std::str::from_utf8(&self.code).expect("currency codes are ASCII")
+    }
+
+    /// Gets the number of minor-unit digits
+    ///
+    /// # Returns
+    /// The exponent, e.g. 2 for USD
+    pub fn exponent(&self) -> u8 {
+        self.exponent
+    }
+}
+
+impl std::fmt::Debug for Currency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Currency")
+            .field("code", &self.code())
+            .field("exponent", &self.exponent)
+            .finish()
+    }
+}
+
+impl Display for Currency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl From<Currency> for String {
+    fn from(currency: Currency) -> Self {
+        currency.code().to_string()
+    }
+}
+
+impl TryFrom<String> for Currency {
+    type Error = Errorsx;
+
+    fn try_from(code: String) -> Result<Self, Self::Error> {
+        Currency::from_code(&code)
+    }
+}
+
+/// Copies a validated three-letter code into a fixed array
+fn code_bytes(code: &str) -> [u8; 3] {
+    let mut bytes = [0u8; 3];
+    bytes.copy_from_slice(code.as_bytes());
+    bytes
+}
+
+/// An exact amount of money
+///
+/// # Fields
+/// * `amount` - The amount in minor units
+/// * `currency` - The currency of the amount
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Money {
+    amount: i64,
+    currency: Currency,
+}
+
+/// Display implementation for Money
+///
+/// Formats the amount with `.` as decimal separator, no grouping and the currency code, e.g.
+/// `-1234.50 EUR`. The output does not depend on the locale.
+impl Display for Money {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.format_amount(), self.currency)
+    }
+}
+
+/// PartialOrd implementation for Money
+///
+/// Amounts in different currencies are not comparable
+impl PartialOrd for Money {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (self.currency == other.currency).then(|| self.amount.cmp(&other.amount))
+    }
+}
+
+impl Money {
+    /// Creates an amount from minor units
+    ///
+    /// # Parameters
+    /// * `amount` - The amount in minor units, e.g. cents
+    /// * `currency` - The currency
+    ///
+    /// # Returns
+    /// A new Money
+    pub fn from_minor(amount: i64, currency: Currency) -> Self {
+        Self { amount, currency }
+    }
+
+    /// Creates a zero amount
+    ///
+    /// # Parameters
+    /// * `currency` - The currency
+    ///
+    /// # Returns
+    /// A Money of zero
+    pub fn zero(currency: Currency) -> Self {
+        Self::from_minor(0, currency)
+    }
+
+    /// Parses a decimal amount such as `12.34` or `-0.5`
+    ///
+    /// # Parameters
+    /// * `value` - The amount in major units with `.` as decimal separator
+    /// * `currency` - The currency
+    ///
+    /// # Returns
+    /// The Money, or an `Errorsx` if the value is malformed, has more decimals than the currency
+    /// allows or does not fit
+    #[track_caller]
+    pub fn parse(value: &str, currency: Currency) -> Result<Self, Errorsx> {
+        let invalid = |reason: &str| {
+            Errorsx::builder("Invalid money amount")
+                .with_context(format!("Value: {}", value))
+                .with_context(format!("Currency: {}", currency))
+                .with_context(reason.to_string())
+                .build()
+        };
+
+        let trimmed = value.trim();
+        let (negative, digits) = match trimmed.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+        };
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        if whole.is_empty() && fraction.is_empty() {
+            return Err(invalid("Expected digits"));
+        }
+        if !whole
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+        {
+            return Err(invalid("Expected digits with an optional `.` separator"));
+        }
+        let exponent = usize::from(currency.exponent);
+        if fraction.len() > exponent {
+            return Err(invalid("Too many decimal places for the currency"));
+        }
+
+        let padded = format!("{}{:0<width$}", whole, fraction, width = exponent);
+        let magnitude: i128 = padded.parse().map_err(|_| invalid("Amount does not fit"))?;
+        let signed = if negative { -magnitude } else { magnitude };
+        let amount = i64::try_from(signed).map_err(|_| invalid("Amount does not fit"))?;
+        Ok(Self { amount, currency })
+    }
+
+    /// Gets the amount in minor units
+    ///
+    /// # Returns
+    /// The amount, e.g. 1050 for 10.50 EUR
+    pub fn amount_minor(&self) -> i64 {
+        self.amount
+    }
+
+    /// Gets the currency
+    ///
+    /// # Returns
+    /// The Currency
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    /// Checks whether the amount is zero
+    ///
+    /// # Returns
+    /// True if the amount is zero
+    pub fn is_zero(&self) -> bool {
+        self.amount == 0
+    }
+
+    /// Checks whether the amount is negative
+    ///
+    /// # Returns
+    /// True if the amount is below zero
+    pub fn is_negative(&self) -> bool {
+        self.amount < 0
+    }
+
+    /// Formats the amount without the currency code
+    ///
+    /// # Returns
+    /// The amount in major units with exactly `exponent` decimals, e.g. `10.50`
+    pub fn format_amount(&self) -> String {
+        let exponent = u32::from(self.currency.exponent);
+        let magnitude = self.amount.unsigned_abs();
+        let sign = if self.amount < 0 { "-" } else { "" };
+        if exponent == 0 {
+            return format!("{}{}", sign, magnitude);
+        }
+        let divisor = 10u64.pow(exponent);
+        format!(
+            "{}{}.{:0width$}",
+            sign,
+            magnitude / divisor,
+            magnitude % divisor,
+            width = exponent as usize
+        )
+    }
+
+    /// Adds two amounts of the same currency
+    ///
+    /// # Parameters
+    /// * `other` - The amount to add
+    ///
+    /// # Returns
+    /// The sum, or an `Errorsx` if the currencies differ or the result overflows
+    #[track_caller]
+    pub fn checked_add(&self, other: &Money) -> Result<Money, Errorsx> {
+        self.ensure_same_currency(other, "add")?;
+        self.amount
+            .checked_add(other.amount)
+            .map(|amount| Self::from_minor(amount, self.currency))
+            .ok_or_else(|| self.overflow("add"))
+    }
+
+    /// Subtracts an amount of the same currency
+    ///
+    /// # Parameters
+    /// * `other` - The amount to subtract
+    ///
+    /// # Returns
+    /// The difference, or an `Errorsx` if the currencies differ or the result overflows
+    #[track_caller]
+    pub fn checked_sub(&self, other: &Money) -> Result<Money, Errorsx> {
+        self.ensure_same_currency(other, "subtract")?;
+        self.amount
+            .checked_sub(other.amount)
+            .map(|amount| Self::from_minor(amount, self.currency))
+            .ok_or_else(|| self.overflow("subtract"))
+    }
+
+    /// Multiplies the amount by an integer factor
+    ///
+    /// Use `allocate` to divide amounts; multiplying by fractions would require rounding.
+    ///
+    /// # Parameters
+    /// * `factor` - The factor, e.g. a quantity
+    ///
+    /// # Returns
+    /// The product, or an `Errorsx` if the result overflows
+    #[track_caller]
+    pub fn checked_mul(&self, factor: i64) -> Result<Money, Errorsx> {
+        self.amount
+            .checked_mul(factor)
+            .map(|amount| Self::from_minor(amount, self.currency))
+            .ok_or_else(|| self.overflow("multiply"))
+    }
+
+    /// Negates the amount
+    ///
+    /// # Returns
+    /// The negated amount, or an `Errorsx` if it overflows
+    #[track_caller]
+    pub fn checked_neg(&self) -> Result<Money, Errorsx> {
+        self.amount
+            .checked_neg()
+            .map(|amount| Self::from_minor(amount, self.currency))
+            .ok_or_else(|| self.overflow("negate"))
+    }
+
+    /// Adds up amounts of one currency
+    ///
+    /// # Parameters
+    /// * `amounts` - The amounts to add
+    /// * `currency` - The currency every amount must have; also the currency of an empty sum
+    ///
+    /// # Returns
+    /// The total, or an `Errorsx` if a currency differs or the total overflows
+    #[track_caller]
+    pub fn checked_sum<'a, I>(amounts: I, currency: Currency) -> Result<Money, Errorsx>
+    where
+        I: IntoIterator<Item = &'a Money>,
+    {
+        amounts
+            .into_iter()
+            .try_fold(Money::zero(currency), |total, amount| {
+                total.checked_add(amount)
+            })
+    }
+
+    /// Splits the amount by ratios without losing minor units
+    ///
+    /// Each part receives its proportional share rounded towards zero; the remaining minor units
+    /// go one each to the parts with the largest remainders, earlier parts winning ties. Negative
+    /// amounts are split like their absolute value and then negated.
+    ///
+    /// # Parameters
+    /// * `ratios` - The relative size of each part, at least one of them non-zero
+    ///
+    /// # Returns
+    /// One amount per ratio, adding up to `self` exactly, or an `Errorsx` if the ratios are
+    /// empty or all zero
+    #[track_caller]
+    pub fn allocate(&self, ratios: &[u32]) -> Result<Vec<Money>, Errorsx> {
+        let total_ratio: u128 = ratios.iter().map(|r| u128::from(*r)).sum();
+        if total_ratio == 0 {
+            return Err(Errorsx::builder("Failed to allocate money")
+                .with_context(format!("Amount: {}", self))
+                .with_context("Ratios must contain at least one non-zero value")
+                .build());
+        }
+
+        let magnitude = u128::from(self.amount.unsigned_abs());
+        let mut shares: Vec<u128> = Vec::with_capacity(ratios.len());
+        let mut remainders: Vec<(usize, u128)> = Vec::with_capacity(ratios.len());
+        for (index, ratio) in ratios.iter().enumerate() {
+            let scaled = magnitude * u128::from(*ratio);
+            shares.push(scaled / total_ratio);
+            remainders.push((index, scaled % total_ratio));
+        }
+
+        let allocated: u128 = shares.iter().sum();
+        let leftover = (magnitude - allocated) as usize;
+        remainders.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        for (index, _) in remainders.into_iter().take(leftover) {
+            shares[index] += 1;
+        }
+
+        Ok(shares
+            .into_iter()
+            .map(|share| {
+                // Every share is at most |amount|, so it fits back into i64 unless the amount is
+                // i64::MIN, whose magnitude still fits when negated.
+                let share = share as i128;
+                let signed = if self.amount < 0 { -share } else { share };
+                Self::from_minor(signed as i64, self.currency)
+            })
+            .collect())
+    }
+
+    /// Splits the amount into equal parts without losing minor units
+    ///
+    /// # Parameters
+    /// * `parts` - The number of parts, at least 1
+    ///
+    /// # Returns
+    /// The parts, earlier parts receiving any extra minor unit, or an `Errorsx` if `parts` is 0
+    #[track_caller]
+    pub fn split(&self, parts: usize) -> Result<Vec<Money>, Errorsx> {
+        self.allocate(&vec![1; parts])
+    }
+
+    #[track_caller]
+    fn ensure_same_currency(&self, other: &Money, operation: &str) -> Result<(), Errorsx> {
+        if self.currency == other.currency {
+            return Ok(());
+        }
+        Err(Errorsx::builder("Currency mismatch")
+            .with_context(format!("Cannot {} {} and {}", operation, self, other))
+            .build())
+    }
+
+    #[track_caller]
+    fn overflow(&self, operation: &str) -> Errorsx {
+        Errorsx::builder("Money arithmetic overflow")
+            .with_context(format!("Failed to {} with {}", operation, self))
+            .build()
+    }
+}
@@ -0,0 +1,268 @@
+//! # Pathx
+//!
+//! This module provides path helpers that work purely on the text of a path: home directory
+//! expansion, lexical normalization of `.` and `..`, containment checks for user-supplied
+//! paths and compact relative display. None of the functions touch the filesystem, so they
+//! behave the same whether or not the paths exist.
+//!
+//! ## Overview
+//!
+//! The main components are:
+//! - `expand_home`: Expands a leading `~` to the user's home directory
+//! - `normalize`: Resolves `.` and `..` components without touching the filesystem
+//! - `is_within`, `resolve_within`: Containment checks for untrusted paths
+//! - `relative_to`, `display_relative`, `display_home`: Helpers for showing paths to users
+//!
+//! ## Usage Scenarios
+//!
+//! Ideal for:
+//! - Rejecting directory traversal (`../../etc/passwd`) in upload and download handlers
+//! - Accepting `~/data` style paths in configuration files and CLI arguments
+//! - Printing short, readable paths in logs and CLI output
+//!
+//! Containment checks are lexical: a symbolic link inside the base directory can still point
+//! elsewhere. Where that matters, canonicalize the base directory once and do not follow links
+//! when opening the resolved path.
+//!
+//! ### Example
+//! ```rust
+//! use std::path::Path;
+//! use x::pathx;
+//!
+//! # fn run() -> Result<(), x::errorsx::Errorsx> {
+//! assert_eq!(pathx::normalize("a/./b/../c"), Path::new("a/c"));
+//!
+//! let uploads = Path::new("/srv/uploads");
+//! assert!(pathx::is_within(uploads, "avatars/ada.png"));
+//! assert!(!pathx::is_within(uploads, "../../etc/passwd"));
+//!
+//! let target = pathx::resolve_within(uploads, "avatars/./ada.png")?;
+//! assert_eq!(target, Path::new("/srv/uploads/avatars/ada.png"));
+//! # Ok(())
+//! # }
+//! # run().unwrap();
+//! ```
+
+use std::path::{Component, Path, PathBuf};
+
+use crate::errorsx::Errorsx;
+
+/// Expands a leading `~` to the user's home directory
+///
+/// Only `~` on its own or followed by a separator is expanded; `~user` forms and paths without
+/// a leading `~` are returned unchanged.
+///
+/// # Parameters
+/// * `path` - The path to expand, e.g. `~/data`
+///
+/// # Returns
+/// The expanded path, or an `Errorsx` if the path starts with `~` and the home directory cannot
+/// be determined
+#[track_caller]
+pub fn expand_home(path: impl AsRef<Path>) -> Result<PathBuf, Errorsx> {
+    let path = path.as_ref();
+    let mut components = path.components();
+    match components.next() {
+        Some(Component::Normal(first)) if first == "~" => {
+            let home = std::env::home_dir().ok_or_else(|| {
+                Errorsx::builder("Failed to determine home directory")
+                    .with_context(format!("Path: {}", path.display()))
+                    .build()
+            })?;
+            let rest = components.as_path();
+            if rest.as_os_str().is_empty() {
+                return Ok(home);
+            }
+            Ok(home.join(rest))
+        }
+        _ => Ok(path.to_path_buf()),
+    }
+}
+
+/// Resolves `.` and `..` components without touching the filesystem
+///
+/// Repeated separators and trailing separators are removed. A `..` directly below the root is
+/// dropped, since the root has no parent; leading `..` components of a relative path are kept.
+/// Symbolic links are not resolved, so the result can differ from `std::fs::canonicalize`.
+///
+/// # Parameters
+/// * `path` - The path to normalize
+///
+/// # Returns
+/// The normalized path, or `.` if a relative path normalizes to nothing
+pub fn normalize(path: impl AsRef<Path>) -> PathBuf {
+    let mut normalized = lexical(path.as_ref());
+    if normalized.as_os_str().is_empty() {
+        normalized.push(Component::CurDir);
+    }
+    normalized
+}
+
+/// Checks whether a path stays inside a base directory
+///
+/// A relative `candidate` is interpreted relative to `base`; an absolute one is checked as-is.
+/// Both are normalized first, so `uploads/../secrets` is not considered within `uploads`.
+///
+/// # Parameters
+/// * `base` - The directory the path must stay within
+/// * `candidate` - The untrusted path
+///
+/// # Returns
+/// `true` if the candidate is the base directory or lies below it
+pub fn is_within(base: impl AsRef<Path>, candidate: impl AsRef<Path>) -> bool {
+    let base = lexical(base.as_ref());
+    contains(&base, &lexical(&base.join(candidate)))
+}
+
+/// Joins an untrusted path onto a base directory, rejecting paths that escape it
+///
+/// # Parameters
+/// * `base` - The directory the path must stay within
+/// * `candidate` - The untrusted path, e.g. a file name from an upload
+///
+/// # Returns
+/// The normalized joined path, or an `Errorsx` with status code 400 if it lies outside `base`
+#[track_caller]
+pub fn resolve_within(
+    base: impl AsRef<Path>,
+    candidate: impl AsRef<Path>,
+) -> Result<PathBuf, Errorsx> {
+    let base = lexical(base.as_ref());
+    let candidate = candidate.as_ref();
+    let resolved = lexical(&base.join(candidate));
+    if contains(&base, &resolved) {
+        return Ok(normalize(resolved));
+    }
+    Err(Errorsx::builder("Path escapes base directory")
+        .with_context(format!("Base: {}", base.display()))
+        .with_context(format!("Path: {}", candidate.display()))
+        .with_status_code(400)
+        .with_status("Bad Request")
+        .build())
+}
+
+/// Computes the path of `path` relative to `base`
+///
+/// Both paths are normalized first. The result may start with `..` components when `path` is
+/// not below `base`.
+///
+/// # Parameters
+/// * `path` - The path to express relatively
+/// * `base` - The directory to express it relative to
+///
+/// # Returns
+/// The relative path, `.` if both are the same, or `None` if one path is absolute and the other
+/// is not, they are on different prefixes, or `base` has leading `..` components that cannot be
+/// resolved lexically
+pub fn relative_to(path: impl AsRef<Path>, base: impl AsRef<Path>) -> Option<PathBuf> {
+    let path = lexical(path.as_ref());
+    let base = lexical(base.as_ref());
+    if path.has_root() != base.has_root() {
+        return None;
+    }
+
+    let mut path_components = path.components().peekable();
+    let mut base_components = base.components().peekable();
+    while let (Some(a), Some(b)) = (path_components.peek(), base_components.peek()) {
+        if a != b {
+            break;
+        }
+        path_components.next();
+        base_components.next();
+    }
+
+    let mut relative = PathBuf::new();
+    for component in base_components {
+        match component {
+            Component::Normal(_) => relative.push(Component::ParentDir),
+            _ => return None,
+        }
+    }
+    for component in path_components {
+        match component {
+            Component::Prefix(_) | Component::RootDir => return None,
+            _ => relative.push(component),
+        }
+    }
+
+    if relative.as_os_str().is_empty() {
+        relative.push(Component::CurDir);
+    }
+    Some(relative)
+}
+
+/// Formats a path relative to a base directory for display
+///
+/// # Parameters
+/// * `path` - The path to display
+/// * `base` - The directory to display it relative to, e.g. the working directory
+///
+/// # Returns
+/// The relative path if `path` lies within `base`, otherwise the full path
+pub fn display_relative(path: impl AsRef<Path>, base: impl AsRef<Path>) -> String {
+    let path = path.as_ref();
+    match relative_to(path, base) {
+        Some(relative) if !relative.starts_with(Component::ParentDir) => {
+            relative.display().to_string()
+        }
+        _ => path.display().to_string(),
+    }
+}
+
+/// Formats a path for display, abbreviating the home directory to `~`
+///
+/// # Parameters
+/// * `path` - The path to display
+///
+/// # Returns
+/// The path with a leading home directory replaced by `~`, otherwise the path unchanged
+pub fn display_home(path: impl AsRef<Path>) -> String {
+    let path = path.as_ref();
+    let Some(home) = std::env::home_dir().filter(|home| home.has_root()) else {
+        return path.display().to_string();
+    };
+    match normalize(path).strip_prefix(normalize(home)) {
+        Ok(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+        Ok(rest) => Path::new("~").join(rest).display().to_string(),
+        Err(_) => path.display().to_string(),
+    }
+}
+
+/// Resolves `.` and `..` components, returning an empty path for a relative path that
+/// normalizes to nothing
+fn lexical(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    // Number of `Normal` components at the end of `normalized` that a `..` may remove
+    let mut depth = 0usize;
+
+    for component in path.components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => normalized.push(component),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if depth > 0 {
+                    normalized.pop();
+                    depth -= 1;
+                } else if !normalized.has_root() {
+                    normalized.push(component);
+                }
+            }
+            Component::Normal(part) => {
+                normalized.push(part);
+                depth += 1;
+            }
+        }
+    }
+    normalized
+}
+
+/// Checks whether a lexically normalized path lies at or below a normalized base
+fn contains(base: &Path, resolved: &Path) -> bool {
+    // A base made of `..` components is a prefix of paths that climb even further up, so
+    // everything after the base must descend.
+    resolved.starts_with(base)
+        && resolved
+            .components()
+            .skip(base.components().count())
+            .all(|component| matches!(component, Component::Normal(_)))
+}
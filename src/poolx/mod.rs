@@ -0,0 +1,380 @@
+//! # Poolx
+//!
+//! This module provides a generic async object pool for resources that are expensive to create
+//! and safe to reuse, such as gRPC channels, SMTP connections or parser instances. Database
+//! connections are better served by the pool that ships with the driver; `Pool` covers
+//! everything else.
+//!
+//! ## Overview
+//!
+//! The main components are:
+//! - `Pool`: A cloneable handle to a pool of objects created on demand by an async factory
+//! - `PoolBuilder`: A builder pattern implementation for configuring a pool
+//! - `PoolGuard`: An RAII guard that returns its object to the pool when dropped
+//!
+//! ## Behaviour
+//!
+//! - At most `max_size` objects are checked out at once; further callers wait for a return
+//! - Idle objects older than `idle_timeout` are discarded instead of being handed out
+//! - An optional health check runs on checkout; objects failing it are discarded and replaced
+//!
+//! ### Example
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use x::poolx::Pool;
+//!
+//! # async fn run() -> Result<(), x::errorsx::Errorsx> {
+//! let pool = Pool::builder(|| async { Ok(String::with_capacity(1024)) })
+//!     .with_max_size(4)
+//!     .with_idle_timeout(Duration::from_secs(60))
+//!     .build();
+//!
+//! let mut buffer = pool.get().await?;
+//! buffer.push_str("reused");
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::VecDeque,
+    fmt::Debug,
+    future::Future,
+    ops::{Deref, DerefMut},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::errorsx::Errorsx;
+
+/// Default maximum number of objects checked out at once
+const DEFAULT_MAX_SIZE: usize = 10;
+
+/// A boxed, sendable future as returned by pool factories and health checks
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+type Factory<T> = Arc<dyn Fn() -> BoxFuture<'static, Result<T, Errorsx>> + Send + Sync>;
+type HealthCheck<T> = Arc<dyn for<'a> Fn(&'a mut T) -> BoxFuture<'a, bool> + Send + Sync>;
+
+/// An object waiting in the pool together with the time it was returned
+struct Idle<T> {
+    object: T,
+    since: Instant,
+}
+
+/// State shared between a pool, its clones and its outstanding guards
+struct Shared<T> {
+    idle: Mutex<VecDeque<Idle<T>>>,
+    permits: Arc<Semaphore>,
+    max_size: usize,
+    idle_timeout: Option<Duration>,
+    factory: Factory<T>,
+    health_check: Option<HealthCheck<T>>,
+}
+
+impl<T> Shared<T> {
+    /// Takes the most recently returned idle object, discarding expired ones
+    fn pop_idle(&self) -> Option<T> {
+        let mut idle = self.idle.lock().unwrap_or_else(|e| e.into_inner());
+        let expired = self.take_expired(&mut idle);
+        let object = idle.pop_back().map(|entry| entry.object);
+        drop(idle);
+        drop(expired);
+        object
+    }
+
+    /// Puts an object back into the idle queue, discarding expired ones
+    fn push_idle(&self, object: T) {
+        let mut idle = self.idle.lock().unwrap_or_else(|e| e.into_inner());
+        let expired = self.take_expired(&mut idle);
+        idle.push_back(Idle {
+            object,
+            since: Instant::now(),
+        });
+        drop(idle);
+        drop(expired);
+    }
+
+    /// Removes expired objects from the front of the idle queue, which holds the oldest returns
+    ///
+    /// The objects are handed back so they can be dropped after the lock is released.
+    fn take_expired(&self, idle: &mut VecDeque<Idle<T>>) -> Vec<Idle<T>> {
+        let Some(timeout) = self.idle_timeout else {
+            return Vec::new();
+        };
+        let mut expired = Vec::new();
+        while idle
+            .front()
+            .is_some_and(|entry| entry.since.elapsed() >= timeout)
+        {
+            expired.extend(idle.pop_front());
+        }
+        expired
+    }
+}
+
+/// Builder for constructing a Pool with a fluent interface
+///
+/// # Fields
+/// * `factory` - Async function creating new objects
+/// * `max_size` - Maximum number of objects checked out at once
+/// * `idle_timeout` - Optional maximum time an object may sit idle before being discarded
+/// * `health_check` - Optional async check run on every checkout of an idle object
+pub struct PoolBuilder<T> {
+    factory: Factory<T>,
+    max_size: usize,
+    idle_timeout: Option<Duration>,
+    health_check: Option<HealthCheck<T>>,
+}
+
+impl<T: Send + 'static> PoolBuilder<T> {
+    /// Creates a new PoolBuilder with the given factory
+    ///
+    /// # Parameters
+    /// * `factory` - Async function creating a new object, or failing with an `Errorsx`
+    ///
+    /// # Returns
+    /// A new PoolBuilder with default settings
+    pub fn new<F, Fut>(factory: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, Errorsx>> + Send + 'static,
+    {
+        Self {
+            factory: Arc::new(move || Box::pin(factory())),
+            max_size: DEFAULT_MAX_SIZE,
+            idle_timeout: None,
+            health_check: None,
+        }
+    }
+
+    /// Sets the maximum number of objects checked out at once
+    ///
+    /// # Parameters
+    /// * `max_size` - The maximum pool size, clamped to at least 1
+    ///
+    /// # Returns
+    /// Self with the maximum size set for chaining
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size.max(1);
+        self
+    }
+
+    /// Sets how long an object may sit idle before it is discarded
+    ///
+    /// # Parameters
+    /// * `idle_timeout` - The maximum idle duration
+    ///
+    /// # Returns
+    /// Self with the idle timeout set for chaining
+    pub fn with_idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = Some(idle_timeout);
+        self
+    }
+
+    /// Sets a health check run when an idle object is checked out
+    ///
+    /// Objects for which the check returns `false` are discarded and the next idle object, or a
+    /// newly created one, is tried instead. Freshly created objects are not checked.
+    ///
+    /// # Parameters
+    /// * `health_check` - Async function returning whether the object is still usable
+    ///
+    /// # Returns
+    /// Self with the health check set for chaining
+    pub fn with_health_check<H>(mut self, health_check: H) -> Self
+    where
+        H: for<'a> Fn(&'a mut T) -> BoxFuture<'a, bool> + Send + Sync + 'static,
+    {
+        self.health_check = Some(Arc::new(health_check));
+        self
+    }
+
+    /// Builds and returns the final Pool instance
+    ///
+    /// # Returns
+    /// An empty Pool; objects are created lazily on checkout
+    pub fn build(self) -> Pool<T> {
+        Pool {
+            shared: Arc::new(Shared {
+                idle: Mutex::new(VecDeque::new()),
+                permits: Arc::new(Semaphore::new(self.max_size)),
+                max_size: self.max_size,
+                idle_timeout: self.idle_timeout,
+                factory: self.factory,
+                health_check: self.health_check,
+            }),
+        }
+    }
+}
+
+/// A generic async object pool
+///
+/// Cloning a Pool is cheap and yields a handle to the same set of objects.
+pub struct Pool<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Clone for Pool<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl<T> Debug for Pool<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Pool")
+            .field("max_size", &self.shared.max_size)
+            .field("in_use", &self.in_use())
+            .field("idle", &self.idle())
+            .finish()
+    }
+}
+
+impl<T: Send + 'static> Pool<T> {
+    /// Creates a new PoolBuilder to construct a pool with more options
+    ///
+    /// # Parameters
+    /// * `factory` - Async function creating a new object, or failing with an `Errorsx`
+    ///
+    /// # Returns
+    /// A PoolBuilder instance for fluent construction
+    pub fn builder<F, Fut>(factory: F) -> PoolBuilder<T>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, Errorsx>> + Send + 'static,
+    {
+        PoolBuilder::new(factory)
+    }
+
+    /// Checks out an object, waiting if the pool is at its maximum size
+    ///
+    /// Idle objects are reused most-recently-returned first. If none are usable a new object is
+    /// created with the factory.
+    ///
+    /// # Returns
+    /// A PoolGuard that returns the object when dropped, or the factory's `Errorsx`
+    pub async fn get(&self) -> Result<PoolGuard<T>, Errorsx> {
+        let permit = Arc::clone(&self.shared.permits)
+            .acquire_owned()
+            .await
+            .map_err(|err| {
+                Errorsx::builder("Failed to check out pooled object")
+                    .with_context("Pool semaphore closed")
+                    .with_source(err)
+                    .build()
+            })?;
+
+        while let Some(mut object) = self.shared.pop_idle() {
+            let healthy = match &self.shared.health_check {
+                Some(check) => check(&mut object).await,
+                None => true,
+            };
+            if healthy {
+                return Ok(self.guard(object, permit));
+            }
+        }
+
+        let object = (self.shared.factory)().await?;
+        Ok(self.guard(object, permit))
+    }
+
+    /// Gets the maximum number of objects checked out at once
+    ///
+    /// # Returns
+    /// The configured maximum size
+    pub fn max_size(&self) -> usize {
+        self.shared.max_size
+    }
+
+    fn guard(&self, object: T, permit: OwnedSemaphorePermit) -> PoolGuard<T> {
+        PoolGuard {
+            object: Some(object),
+            shared: Arc::clone(&self.shared),
+            _permit: permit,
+        }
+    }
+}
+
+impl<T> Pool<T> {
+    /// Gets the number of objects currently checked out
+    ///
+    /// # Returns
+    /// The number of outstanding guards
+    pub fn in_use(&self) -> usize {
+        self.shared.max_size - self.shared.permits.available_permits()
+    }
+
+    /// Gets the number of objects waiting in the pool
+    ///
+    /// Expired objects are removed on checkout and return, so they may still be counted here.
+    ///
+    /// # Returns
+    /// The number of idle objects
+    pub fn idle(&self) -> usize {
+        self.shared
+            .idle
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .len()
+    }
+}
+
+/// An object checked out of a Pool
+///
+/// Dereferences to the pooled object and returns it to the pool when dropped.
+///
+/// # Fields
+/// * `object` - The pooled object, taken out on drop or discard
+/// * `shared` - The pool the object is returned to
+/// * `_permit` - Checkout slot held for as long as the guard lives
+pub struct PoolGuard<T> {
+    object: Option<T>,
+    shared: Arc<Shared<T>>,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl<T> PoolGuard<T> {
+    /// Drops the object instead of returning it to the pool
+    ///
+    /// Use this when the object is known to be broken, e.g. after a connection error.
+    pub fn discard(mut self) {
+        self.object.take();
+    }
+}
+
+impl<T> Deref for PoolGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.object
+            .as_ref()
+            .expect("pooled object present until drop")
+    }
+}
+
+impl<T> DerefMut for PoolGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.object
+            .as_mut()
+            .expect("pooled object present until drop")
+    }
+}
+
+impl<T: Debug> Debug for PoolGuard<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("PoolGuard").field(&self.object).finish()
+    }
+}
+
+impl<T> Drop for PoolGuard<T> {
+    fn drop(&mut self) {
+        if let Some(object) = self.object.take() {
+            self.shared.push_idle(object);
+        }
+    }
+}
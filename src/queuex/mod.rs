@@ -0,0 +1,785 @@
+//! # Queuex
+//!
+//! This module provides an in-process job queue for background work that does not need to
+//! survive a restart. Jobs carry a typed payload and are processed by a fixed number of workers
+//! running a single handler. Failed jobs are retried with backoff up to a maximum number of
+//! attempts; jobs that still fail are moved to a dead-letter collection instead of being lost.
+//!
+//! ## Overview
+//!
+//! The main components are:
+//! - `Queue` / `QueueBuilder`: The queue and its configuration (workers, attempts, backoff)
+//! - `Job`: A payload together with its ID and attempt number, as seen by the handler
+//! - `JobOptions`: Per-job delay, maximum attempts and backoff
+//! - `Backoff`: The delay between attempts
+//! - `DeadLetter`: A job that failed permanently, with its payload and last error
+//!
+//! ## Shutdown
+//!
+//! - `drain` stops accepting jobs and waits until every queued, delayed and retried job has
+//!   finished; the dead letters are returned as an `ErrorsxGroup`
+//! - `shutdown` stops accepting jobs, waits only for running jobs and returns the payloads of
+//!   jobs that never ran
+//!
+//! Call either after a shutdown signal, e.g. `tokio::signal::ctrl_c()`, optionally bounded with
+//! `tokio::time::timeout`. Dropping the last handle stops the workers after their current job.
+//!
+//! ### Example
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use x::errorsx::Errorsx;
+//! use x::queuex::{Backoff, Job, JobOptions, Queue};
+//!
+//! # async fn run() -> Result<(), Errorsx> {
+//! let queue = Queue::builder(|job: Job<String>| async move {
+//!     println!("sending welcome email to {}", job.payload());
+//!     Ok::<_, Errorsx>(())
+//! })
+//! .with_workers(4)
+//! .with_max_attempts(5)
+//! .with_backoff(Backoff::exponential(Duration::from_secs(1), Duration::from_secs(60)))
+//! .build();
+//!
+//! queue.enqueue("ada@example.com".to_string())?;
+//! queue.enqueue_with(
+//!     "grace@example.com".to_string(),
+//!     JobOptions::new().with_delay(Duration::from_secs(30)),
+//! )?;
+//!
+//! if let Err(dead_letters) = queue.drain().await {
+//!     eprintln!("{} jobs failed permanently", dead_letters.len());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Display,
+    future::Future,
+    panic::AssertUnwindSafe,
+    pin::pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures::{
+    future::{self, BoxFuture},
+    FutureExt,
+};
+use tokio::{sync::Notify, task::JoinHandle, time::Instant};
+
+use crate::errorsx::{Errorsx, ErrorsxGroup};
+
+/// Due time offset used when a delay is too large to represent as an `Instant`
+const FAR_FUTURE: Duration = Duration::from_secs(86_400 * 365 * 30);
+
+/// Handler processing one job attempt
+type Handler<T> = Arc<dyn Fn(Job<T>) -> BoxFuture<'static, Result<(), Errorsx>> + Send + Sync>;
+
+/// The delay between attempts of a failed job
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backoff {
+    /// Retry immediately
+    None,
+    /// Wait the same time before every retry
+    Fixed(Duration),
+    /// Double the wait after every failure, starting at `initial` and capped at `max`
+    Exponential { initial: Duration, max: Duration },
+}
+
+impl Backoff {
+    /// Creates a fixed backoff
+    ///
+    /// # Parameters
+    /// * `delay` - The wait before every retry
+    ///
+    /// # Returns
+    /// A fixed Backoff
+    pub fn fixed(delay: Duration) -> Self {
+        Backoff::Fixed(delay)
+    }
+
+    /// Creates an exponential backoff
+    ///
+    /// # Parameters
+    /// * `initial` - The wait after the first failure
+    /// * `max` - The longest wait
+    ///
+    /// # Returns
+    /// An exponential Backoff
+    pub fn exponential(initial: Duration, max: Duration) -> Self {
+        Backoff::Exponential { initial, max }
+    }
+
+    /// Computes the wait before the next attempt
+    ///
+    /// # Parameters
+    /// * `failures` - The number of failed attempts so far, starting at 1
+    ///
+    /// # Returns
+    /// The delay before the next attempt
+    pub fn delay(&self, failures: u32) -> Duration {
+        match *self {
+            Backoff::None => Duration::ZERO,
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential { initial, max } => {
+                let factor = 2u32.saturating_pow(failures.saturating_sub(1));
+                initial.saturating_mul(factor).min(max)
+            }
+        }
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff::Exponential {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+/// The identifier of an enqueued job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct JobId(u64);
+
+impl Display for JobId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A job attempt as passed to the handler
+#[derive(Debug, Clone)]
+pub struct Job<T> {
+    id: JobId,
+    attempt: u32,
+    payload: T,
+}
+
+impl<T> Job<T> {
+    /// Gets the job ID
+    ///
+    /// # Returns
+    /// The JobId, which stays the same across attempts
+    pub fn id(&self) -> JobId {
+        self.id
+    }
+
+    /// Gets the attempt number
+    ///
+    /// # Returns
+    /// The attempt number, starting at 1
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// Gets the payload
+    ///
+    /// # Returns
+    /// A reference to the payload
+    pub fn payload(&self) -> &T {
+        &self.payload
+    }
+
+    /// Consumes the job and returns the payload
+    ///
+    /// # Returns
+    /// The payload
+    pub fn into_payload(self) -> T {
+        self.payload
+    }
+}
+
+/// Per-job settings overriding the queue defaults
+#[derive(Debug, Clone, Default)]
+pub struct JobOptions {
+    delay: Option<Duration>,
+    max_attempts: Option<u32>,
+    backoff: Option<Backoff>,
+}
+
+impl JobOptions {
+    /// Creates new JobOptions using the queue defaults
+    ///
+    /// # Returns
+    /// New JobOptions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Delays the first attempt
+    ///
+    /// # Parameters
+    /// * `delay` - The time to wait before the job becomes runnable
+    ///
+    /// # Returns
+    /// The JobOptions instance
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Sets the maximum number of attempts for this job
+    ///
+    /// # Parameters
+    /// * `max_attempts` - The maximum number of attempts, at least 1
+    ///
+    /// # Returns
+    /// The JobOptions instance
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = Some(max_attempts.max(1));
+        self
+    }
+
+    /// Sets the backoff for this job
+    ///
+    /// # Parameters
+    /// * `backoff` - The delay between attempts
+    ///
+    /// # Returns
+    /// The JobOptions instance
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = Some(backoff);
+        self
+    }
+}
+
+/// A job that failed on every attempt
+///
+/// # Fields
+/// * `id` - The job ID
+/// * `payload` - The payload, so the job can be inspected or enqueued again
+/// * `attempts` - The number of attempts made
+/// * `error` - The error of the last attempt, with the job ID and attempts in its context
+#[derive(Debug)]
+pub struct DeadLetter<T> {
+    pub id: JobId,
+    pub payload: T,
+    pub attempts: u32,
+    pub error: Errorsx,
+}
+
+/// A job waiting to run
+struct Entry<T> {
+    id: JobId,
+    attempt: u32,
+    payload: T,
+    max_attempts: u32,
+    backoff: Backoff,
+}
+
+/// Mutable queue state
+struct State<T> {
+    ready: VecDeque<Entry<T>>,
+    delayed: BTreeMap<(Instant, JobId), Entry<T>>,
+    dead_letters: Vec<DeadLetter<T>>,
+    in_flight: usize,
+    next_id: u64,
+    closed: bool,
+    stopping: bool,
+}
+
+impl<T> State<T> {
+    /// Moves delayed jobs that are due to the ready queue
+    fn promote_due(&mut self, now: Instant) {
+        while let Some(entry) = self.delayed.first_entry() {
+            if entry.key().0 > now {
+                break;
+            }
+            self.ready.push_back(entry.remove());
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        self.ready.is_empty() && self.delayed.is_empty() && self.in_flight == 0
+    }
+}
+
+/// State shared between handles and workers
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    handler: Handler<T>,
+    max_attempts: u32,
+    backoff: Backoff,
+    /// Wakes workers when jobs are added or the queue stops
+    work: Notify,
+    /// Wakes `drain` and `shutdown` when a job finishes
+    progress: Notify,
+    workers: Mutex<Vec<JoinHandle<()>>>,
+}
+
+/// Stops the workers once the last Queue handle is dropped
+struct Handle<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Drop for Handle<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.closed = true;
+        state.stopping = true;
+        drop(state);
+        self.shared.work.notify_waiters();
+    }
+}
+
+/// Builder for creating Queue instances
+pub struct QueueBuilder<T> {
+    handler: Handler<T>,
+    workers: usize,
+    max_attempts: u32,
+    backoff: Backoff,
+}
+
+impl<T: Send + 'static> QueueBuilder<T> {
+    /// Sets the number of jobs processed concurrently
+    ///
+    /// # Parameters
+    /// * `workers` - The number of workers, at least 1 (default: 1)
+    ///
+    /// # Returns
+    /// The QueueBuilder instance
+    pub fn with_workers(mut self, workers: usize) -> Self {
+        self.workers = workers.max(1);
+        self
+    }
+
+    /// Sets the default maximum number of attempts per job
+    ///
+    /// # Parameters
+    /// * `max_attempts` - The maximum number of attempts, at least 1 (default: 3)
+    ///
+    /// # Returns
+    /// The QueueBuilder instance
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Sets the default backoff between attempts
+    ///
+    /// # Parameters
+    /// * `backoff` - The Backoff (default: exponential from 1 second up to 5 minutes)
+    ///
+    /// # Returns
+    /// The QueueBuilder instance
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Builds the Queue and starts its workers
+    ///
+    /// # Panics
+    /// Panics if called outside of a Tokio runtime.
+    ///
+    /// # Returns
+    /// The running Queue
+    pub fn build(self) -> Queue<T>
+    where
+        T: Clone + Sync,
+    {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                ready: VecDeque::new(),
+                delayed: BTreeMap::new(),
+                dead_letters: Vec::new(),
+                in_flight: 0,
+                next_id: 1,
+                closed: false,
+                stopping: false,
+            }),
+            handler: self.handler,
+            max_attempts: self.max_attempts,
+            backoff: self.backoff,
+            work: Notify::new(),
+            progress: Notify::new(),
+            workers: Mutex::new(Vec::new()),
+        });
+
+        let workers = (0..self.workers)
+            .map(|_| tokio::spawn(work(shared.clone())))
+            .collect();
+        *shared.workers.lock().unwrap_or_else(|e| e.into_inner()) = workers;
+
+        Queue {
+            handle: Arc::new(Handle { shared }),
+        }
+    }
+}
+
+/// An in-memory job queue
+///
+/// Clones share the same queue, so producers can each hold a handle.
+pub struct Queue<T> {
+    handle: Arc<Handle<T>>,
+}
+
+impl<T> Clone for Queue<T> {
+    fn clone(&self) -> Self {
+        Self {
+            handle: self.handle.clone(),
+        }
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> Queue<T> {
+    /// Creates a new QueueBuilder
+    ///
+    /// The handler is called once per attempt. Returning an error, or panicking, fails the
+    /// attempt.
+    ///
+    /// # Parameters
+    /// * `handler` - Processes one job attempt
+    ///
+    /// # Returns
+    /// A new QueueBuilder
+    pub fn builder<F, Fut>(handler: F) -> QueueBuilder<T>
+    where
+        F: Fn(Job<T>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), Errorsx>> + Send + 'static,
+    {
+        QueueBuilder {
+            handler: Arc::new(move |job| handler(job).boxed()),
+            workers: 1,
+            max_attempts: 3,
+            backoff: Backoff::default(),
+        }
+    }
+
+    /// Adds a job that runs as soon as a worker is free
+    ///
+    /// # Parameters
+    /// * `payload` - The job payload
+    ///
+    /// # Returns
+    /// The JobId, or an `Errorsx` with status code 503 if the queue no longer accepts jobs
+    #[track_caller]
+    pub fn enqueue(&self, payload: T) -> Result<JobId, Errorsx> {
+        self.enqueue_with(payload, JobOptions::default())
+    }
+
+    /// Adds a job with its own delay, maximum attempts or backoff
+    ///
+    /// # Parameters
+    /// * `payload` - The job payload
+    /// * `options` - The JobOptions
+    ///
+    /// # Returns
+    /// The JobId, or an `Errorsx` with status code 503 if the queue no longer accepts jobs
+    #[track_caller]
+    pub fn enqueue_with(&self, payload: T, options: JobOptions) -> Result<JobId, Errorsx> {
+        let shared = &self.handle.shared;
+        let mut state = shared.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.closed {
+            return Err(Errorsx::builder("Queue is not accepting jobs")
+                .with_status_code(503)
+                .with_status("Service Unavailable")
+                .build());
+        }
+
+        let id = JobId(state.next_id);
+        state.next_id += 1;
+        let entry = Entry {
+            id,
+            attempt: 1,
+            payload,
+            max_attempts: options.max_attempts.unwrap_or(shared.max_attempts),
+            backoff: options.backoff.unwrap_or(shared.backoff),
+        };
+        match options.delay.filter(|delay| !delay.is_zero()) {
+            Some(delay) => {
+                state.delayed.insert((due_at(delay), id), entry);
+            }
+            None => state.ready.push_back(entry),
+        }
+        drop(state);
+
+        shared.work.notify_waiters();
+        Ok(id)
+    }
+
+    /// Gets the number of jobs waiting to run, including delayed jobs and pending retries
+    ///
+    /// # Returns
+    /// The number of waiting jobs
+    pub fn pending(&self) -> usize {
+        let state = self.state();
+        state.ready.len() + state.delayed.len()
+    }
+
+    /// Gets the number of jobs currently being processed
+    ///
+    /// # Returns
+    /// The number of running jobs
+    pub fn in_flight(&self) -> usize {
+        self.state().in_flight
+    }
+
+    /// Takes the jobs that failed permanently so far
+    ///
+    /// # Returns
+    /// The dead letters, oldest first; the collection is empty afterwards
+    pub fn take_dead_letters(&self) -> Vec<DeadLetter<T>> {
+        std::mem::take(&mut self.state().dead_letters)
+    }
+
+    /// Stops accepting new jobs
+    ///
+    /// Jobs already queued keep running.
+    pub fn close(&self) {
+        self.state().closed = true;
+    }
+
+    /// Stops accepting jobs and waits until every queued job has finished
+    ///
+    /// Delayed jobs and retries are waited for as well, so bound the wait with
+    /// `tokio::time::timeout` when jobs may be scheduled far in the future.
+    ///
+    /// # Returns
+    /// `Ok(())` if no job failed permanently, otherwise the errors of all dead letters not yet
+    /// taken with `take_dead_letters`
+    pub async fn drain(&self) -> Result<(), ErrorsxGroup> {
+        self.close();
+        self.wait_until(|state| state.is_idle()).await;
+        self.stop().await;
+
+        let errors: Vec<Errorsx> = self
+            .take_dead_letters()
+            .into_iter()
+            .map(|dead_letter| dead_letter.error)
+            .collect();
+        ErrorsxGroup::from(errors).into_result()
+    }
+
+    /// Stops accepting jobs and waits for running jobs only
+    ///
+    /// Running jobs that fail are not retried; their payloads are returned along with the jobs
+    /// that never ran, unless they used up their attempts.
+    ///
+    /// # Returns
+    /// The payloads of jobs that were queued, delayed or waiting for a retry
+    pub async fn shutdown(&self) -> Vec<T> {
+        {
+            let mut state = self.state();
+            state.closed = true;
+            state.stopping = true;
+        }
+        self.handle.shared.work.notify_waiters();
+        self.wait_until(|state| state.in_flight == 0).await;
+        self.stop().await;
+
+        let mut state = self.state();
+        let mut remaining: Vec<T> = state.ready.drain(..).map(|entry| entry.payload).collect();
+        remaining.extend(
+            std::mem::take(&mut state.delayed)
+                .into_values()
+                .map(|entry| entry.payload),
+        );
+        remaining
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State<T>> {
+        self.handle
+            .shared
+            .state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Waits until the state satisfies a condition
+    async fn wait_until(&self, condition: impl Fn(&State<T>) -> bool) {
+        let shared = &self.handle.shared;
+        loop {
+            let mut progressed = pin!(shared.progress.notified());
+            progressed.as_mut().enable();
+            if condition(&self.state()) {
+                return;
+            }
+            progressed.await;
+        }
+    }
+
+    /// Stops the workers and waits for them to exit
+    async fn stop(&self) {
+        let shared = &self.handle.shared;
+        self.state().stopping = true;
+        shared.work.notify_waiters();
+
+        let workers =
+            std::mem::take(&mut *shared.workers.lock().unwrap_or_else(|e| e.into_inner()));
+        for worker in workers {
+            let _ = worker.await;
+        }
+    }
+}
+
+/// Runs jobs until the queue stops
+async fn work<T: Clone + Send + Sync + 'static>(shared: Arc<Shared<T>>) {
+    loop {
+        let mut woken = pin!(shared.work.notified());
+        woken.as_mut().enable();
+
+        let next = {
+            let mut state = shared.state.lock().unwrap_or_else(|e| e.into_inner());
+            if state.stopping {
+                return;
+            }
+            state.promote_due(Instant::now());
+            match state.ready.pop_front() {
+                Some(entry) => {
+                    state.in_flight += 1;
+                    Ok(entry)
+                }
+                None => Err(state.delayed.keys().next().map(|(due, _)| *due)),
+            }
+        };
+
+        match next {
+            Ok(entry) => run(&shared, entry).await,
+            Err(Some(due)) => {
+                let sleep = pin!(tokio::time::sleep_until(due));
+                let _ = future::select(woken, sleep).await;
+            }
+            Err(None) => woken.await,
+        }
+    }
+}
+
+/// Runs one attempt of a job and records its outcome
+async fn run<T: Clone>(shared: &Shared<T>, entry: Entry<T>) {
+    let job = Job {
+        id: entry.id,
+        attempt: entry.attempt,
+        payload: entry.payload.clone(),
+    };
+    // Creating the future runs the handler's synchronous part, which may panic as well
+    let attempt = async { (shared.handler)(job).await };
+    let outcome = match AssertUnwindSafe(attempt).catch_unwind().await {
+        Ok(result) => result,
+        Err(_) => Err(Errorsx::builder("Job handler panicked").build()),
+    };
+
+    let mut state = shared.state.lock().unwrap_or_else(|e| e.into_inner());
+    state.in_flight -= 1;
+    let mut rescheduled = false;
+    if let Err(error) = outcome {
+        if entry.attempt < entry.max_attempts {
+            let delay = entry.backoff.delay(entry.attempt);
+            tracing::debug!(job_id = %entry.id, attempt = entry.attempt, ?delay, "Job failed, retrying");
+            let retry = Entry {
+                attempt: entry.attempt + 1,
+                ..entry
+            };
+            state.delayed.insert((due_at(delay), retry.id), retry);
+            rescheduled = true;
+        } else {
+            tracing::warn!(job_id = %entry.id, attempts = entry.attempt, "Job failed permanently");
+            let error = Errorsx::builder("Job failed permanently")
+                .with_context(format!("Job ID: {}", entry.id))
+                .with_context(format!("Attempts: {}", entry.attempt))
+                .with_source(error)
+                .build();
+            state.dead_letters.push(DeadLetter {
+                id: entry.id,
+                payload: entry.payload,
+                attempts: entry.attempt,
+                error,
+            });
+        }
+    }
+    drop(state);
+
+    if rescheduled {
+        shared.work.notify_waiters();
+    }
+    shared.progress.notify_waiters();
+}
+
+/// Computes when a job delayed from now becomes runnable
+fn due_at(delay: Duration) -> Instant {
+    let now = Instant::now();
+    now.checked_add(delay).unwrap_or_else(|| now + FAR_FUTURE)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// Builds a single-worker queue whose handler panics on payload 1, before or after
+    /// returning its future, and counts the jobs that ran to completion
+    fn panicking_queue(synchronous: bool) -> (Queue<u32>, Arc<AtomicUsize>) {
+        let completed = Arc::new(AtomicUsize::new(0));
+        let counter = completed.clone();
+        let queue = Queue::builder(move |job: Job<u32>| {
+            if synchronous && *job.payload() == 1 {
+                panic!("synchronous handler panic");
+            }
+            let counter = counter.clone();
+            async move {
+                if *job.payload() == 1 {
+                    panic!("asynchronous handler panic");
+                }
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }
+        })
+        .with_max_attempts(1)
+        .build();
+        (queue, completed)
+    }
+
+    async fn assert_panic_fails_attempt(synchronous: bool) {
+        let (queue, completed) = panicking_queue(synchronous);
+        queue.enqueue(1).unwrap();
+        queue.enqueue(2).unwrap();
+
+        let drained = tokio::time::timeout(Duration::from_secs(3), queue.drain())
+            .await
+            .expect("drain did not finish");
+
+        let dead_letters = drained.unwrap_err();
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(queue.in_flight(), 0);
+        assert_eq!(completed.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn synchronous_handler_panic_fails_attempt() {
+        assert_panic_fails_attempt(true).await;
+    }
+
+    #[tokio::test]
+    async fn asynchronous_handler_panic_fails_attempt() {
+        assert_panic_fails_attempt(false).await;
+    }
+
+    #[tokio::test]
+    async fn huge_delays_do_not_overflow() {
+        let queue = Queue::builder(|_: Job<u32>| async { Err(Errorsx::builder("fail").build()) })
+            .with_max_attempts(2)
+            .with_backoff(Backoff::fixed(Duration::MAX))
+            .build();
+
+        queue
+            .enqueue_with(1, JobOptions::new().with_delay(Duration::MAX))
+            .unwrap();
+        queue.enqueue(2).unwrap();
+
+        tokio::time::timeout(Duration::from_secs(3), async {
+            while queue.pending() < 2 || queue.in_flight() > 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("retry was not scheduled");
+
+        let mut remaining = queue.shutdown().await;
+        remaining.sort();
+        assert_eq!(remaining, vec![1, 2]);
+    }
+}
@@ -0,0 +1,94 @@
+//! Tower middleware propagating request IDs
+//!
+//! This module provides `RequestIdLayer`, available with the `tower` feature. For every request
+//! it reads `X-Request-Id` or generates a new ID, stores the `RequestId` in the request
+//! extensions, runs the inner service inside `scope` and a tracing span carrying the ID, and
+//! sets the same header on the response.
+
+use std::task::{Context, Poll};
+
+use futures::future::BoxFuture;
+use hyper::{
+    header::{HeaderName, HeaderValue},
+    Request, Response,
+};
+use tower::{Layer, Service};
+use tracing::Instrument;
+
+use super::{scope, RequestId, CURRENT, REQUEST_ID_HEADER};
+
+/// Layer adding request ID propagation to services
+#[derive(Debug, Clone, Default)]
+pub struct RequestIdLayer;
+
+impl RequestIdLayer {
+    /// Creates a new RequestIdLayer
+    ///
+    /// # Returns
+    /// A new RequestIdLayer
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<S> Layer<S> for RequestIdLayer {
+    type Service = RequestIdService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestIdService { inner }
+    }
+}
+
+/// Service produced by RequestIdLayer
+#[derive(Debug, Clone)]
+pub struct RequestIdService<S> {
+    inner: S,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for RequestIdService<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<ReqBody>) -> Self::Future {
+        let header = HeaderName::from_static(REQUEST_ID_HEADER);
+        let id = RequestId::parse_or_generate(
+            request
+                .headers()
+                .get(&header)
+                .and_then(|value| value.to_str().ok()),
+        );
+        request.extensions_mut().insert(id.clone());
+
+        let span = tracing::info_span!(
+            "request",
+            request_id = %id,
+            method = %request.method(),
+            uri = %request.uri(),
+        );
+        let future = {
+            let _entered = span.enter();
+            CURRENT.sync_scope(id.clone(), || self.inner.call(request))
+        };
+        let future = scope(id.clone(), future);
+
+        Box::pin(
+            async move {
+                let mut response = future.await?;
+                if let Ok(value) = HeaderValue::from_str(id.as_str()) {
+                    response.headers_mut().insert(header, value);
+                }
+                Ok(response)
+            }
+            .instrument(span),
+        )
+    }
+}
@@ -0,0 +1,125 @@
+//! # Requestidx
+//!
+//! This module provides request ID generation and propagation so that logs, errors and responses
+//! belonging to one request can be correlated end-to-end. An incoming `X-Request-Id` header is
+//! reused when it looks sane, otherwise a time-ordered UUID v7 is generated. The ID is kept in
+//! task-local storage for the duration of the request, where any code can read it with
+//! `current`.
+//!
+//! ## Overview
+//!
+//! The main components are:
+//! - `RequestId`: A validated request ID
+//! - `scope`: Runs a future with a request ID available through `current`
+//! - `current`: Gets the request ID of the running request, if any
+//! - `layer::RequestIdLayer`: Tower middleware reading or generating the ID, recording it on a
+//!   tracing span and echoing it on the response (requires the `tower` feature)
+//!
+//! ### Example
+//! ```rust
+//! use x::requestidx::{self, RequestId};
+//!
+//! # futures::executor::block_on(async {
+//! let id = RequestId::generate();
+//! requestidx::scope(id.clone(), async move {
+//!     assert_eq!(requestidx::current(), Some(id));
+//! })
+//! .await;
+//! # });
+//! ```
+#[cfg(feature = "tower")]
+pub mod layer;
+
+use std::{fmt::Display, future::Future};
+
+use crate::uuidx;
+
+/// Name of the header carrying the request ID
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest incoming request ID that is accepted as-is
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static CURRENT: RequestId;
+}
+
+/// An identifier correlating everything that happens for one request
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RequestId(String);
+
+impl RequestId {
+    /// Generates a new request ID from a UUID v7
+    ///
+    /// # Returns
+    /// A new RequestId that sorts by creation time
+    pub fn generate() -> Self {
+        Self(uuidx::generate_new_v7().to_string())
+    }
+
+    /// Accepts a request ID supplied by a client or upstream proxy
+    ///
+    /// IDs must be non-empty, at most 128 characters and consist of visible ASCII only, so they
+    /// are safe to log and to echo in a header.
+    ///
+    /// # Parameters
+    /// * `value` - The supplied ID
+    ///
+    /// # Returns
+    /// The RequestId, or `None` if the value is not acceptable
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let valid = !value.is_empty()
+            && value.len() <= MAX_REQUEST_ID_LEN
+            && value.bytes().all(|b| b.is_ascii_graphic());
+        valid.then(|| Self(value.to_string()))
+    }
+
+    /// Reuses a supplied request ID if acceptable, otherwise generates a new one
+    ///
+    /// # Parameters
+    /// * `value` - The supplied ID, if any
+    ///
+    /// # Returns
+    /// A RequestId
+    pub fn parse_or_generate(value: Option<&str>) -> Self {
+        value.and_then(Self::parse).unwrap_or_else(Self::generate)
+    }
+
+    /// Gets the request ID as a string slice
+    ///
+    /// # Returns
+    /// The ID
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Runs a future with the given request ID available through `current`
+///
+/// # Parameters
+/// * `id` - The request ID
+/// * `future` - The future to run
+///
+/// # Returns
+/// The future's output
+pub async fn scope<F: Future>(id: RequestId, future: F) -> F::Output {
+    CURRENT.scope(id, future).await
+}
+
+/// Gets the request ID of the running request
+///
+/// Task-local storage does not cross `tokio::spawn`; pass the ID explicitly or wrap the spawned
+/// future in `scope` to propagate it.
+///
+/// # Returns
+/// The current RequestId, or `None` outside of `scope`
+pub fn current() -> Option<RequestId> {
+    CURRENT.try_with(|id| id.clone()).ok()
+}
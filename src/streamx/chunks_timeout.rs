@@ -0,0 +1,126 @@
+//! Size- and time-bounded batching
+//!
+//! This module provides `ChunksTimeout`, a stream adapter that collects items into batches and
+//! emits a batch as soon as it is full or as soon as its oldest item has waited for the
+//! configured duration. A partial batch is flushed when the underlying stream ends.
+
+use std::{
+    future::Future,
+    mem,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures::{ready, Stream};
+use pin_project_lite::pin_project;
+use tokio::time::{Instant, Sleep};
+
+/// Deadline offset used when the batch duration is too large to represent as an `Instant`
+const FAR_FUTURE: Duration = Duration::from_secs(86_400 * 365 * 30);
+
+/// Largest number of items preallocated for a batch; bigger batches grow as items arrive
+const MAX_PREALLOCATED: usize = 1024;
+
+pin_project! {
+    /// Stream returned by `StreamxExt::chunks_timeout`
+    #[must_use = "streams do nothing unless polled"]
+    #[derive(Debug)]
+    pub struct ChunksTimeout<S: Stream> {
+        #[pin]
+        stream: S,
+        deadline: Option<Pin<Box<Sleep>>>,
+        items: Vec<S::Item>,
+        capacity: usize,
+        duration: Duration,
+        done: bool,
+    }
+}
+
+impl<S: Stream> ChunksTimeout<S> {
+    /// Creates a new ChunksTimeout adapter
+    ///
+    /// # Arguments
+    /// * `stream` - The stream to batch
+    /// * `capacity` - Maximum number of items per batch
+    /// * `duration` - Maximum time the first item of a batch waits before the batch is emitted
+    ///
+    /// # Returns
+    /// * The batching stream
+    ///
+    /// # Panics
+    /// * If `capacity` is zero
+    pub fn new(stream: S, capacity: usize, duration: Duration) -> Self {
+        assert!(
+            capacity > 0,
+            "chunks_timeout capacity must be greater than zero"
+        );
+        Self {
+            stream,
+            deadline: None,
+            items: batch_buffer(capacity),
+            capacity,
+            duration,
+            done: false,
+        }
+    }
+}
+
+impl<S: Stream> Stream for ChunksTimeout<S> {
+    type Item = Vec<S::Item>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        while !*this.done {
+            match this.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    if this.items.is_empty() {
+                        let now = Instant::now();
+                        let at = now
+                            .checked_add(*this.duration)
+                            .unwrap_or_else(|| now + FAR_FUTURE);
+                        match this.deadline {
+                            Some(deadline) => deadline.as_mut().reset(at),
+                            None => *this.deadline = Some(Box::pin(tokio::time::sleep_until(at))),
+                        }
+                    }
+                    this.items.push(item);
+                    if this.items.len() >= *this.capacity {
+                        let batch = mem::replace(this.items, batch_buffer(*this.capacity));
+                        return Poll::Ready(Some(batch));
+                    }
+                }
+                Poll::Ready(None) => *this.done = true,
+                Poll::Pending => break,
+            }
+        }
+
+        if this.items.is_empty() {
+            return if *this.done {
+                Poll::Ready(None)
+            } else {
+                Poll::Pending
+            };
+        }
+
+        if let (false, Some(deadline)) = (*this.done, this.deadline.as_mut()) {
+            ready!(deadline.as_mut().poll(cx));
+        }
+        let batch = mem::replace(this.items, batch_buffer(*this.capacity));
+        Poll::Ready(Some(batch))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let buffered = usize::from(!self.items.is_empty());
+        let (lower, upper) = self.stream.size_hint();
+        let lower = lower / self.capacity;
+        let upper = upper.and_then(|upper| upper.checked_add(buffered));
+        (lower, upper)
+    }
+}
+
+/// Allocates the buffer for a new batch
+fn batch_buffer<T>(capacity: usize) -> Vec<T> {
+    Vec::with_capacity(capacity.min(MAX_PREALLOCATED))
+}
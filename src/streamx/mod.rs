@@ -0,0 +1,108 @@
+//! # Streamx
+//!
+//! This module provides batching and throttling combinators for `futures::Stream`, covering the
+//! patterns event consumers otherwise implement by hand: flushing a batch when it is full or has
+//! waited long enough, capping throughput, and running a bounded number of fallible tasks that
+//! stop at the first failure.
+//!
+//! The module exposes three sub-modules, one per combinator, and the `StreamxExt` extension
+//! trait which makes them available as methods on any stream:
+//! - `chunks_timeout`: Emits a batch when it reaches a size or when a duration has elapsed
+//! - `rate_limit`: Yields at most a fixed number of items per second
+//! - `try_buffer_unordered_with_limit`: Runs fallible futures concurrently, stopping on error
+//!
+//! The timed combinators rely on Tokio timers and must be polled from within a Tokio runtime.
+//!
+//! # Example
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use futures::StreamExt;
+//! use x::streamx::StreamxExt;
+//!
+//! # async fn run(events: impl futures::Stream<Item = String> + Unpin) {
+//! let mut batches = events.chunks_timeout(100, Duration::from_millis(500));
+//! while let Some(batch) = batches.next().await {
+//!     println!("flushing {} events", batch.len());
+//! }
+//! # }
+//! ```
+pub mod chunks_timeout;
+pub mod rate_limit;
+pub mod try_buffer_unordered_with_limit;
+
+use std::{future::Future, time::Duration};
+
+use futures::Stream;
+
+pub use chunks_timeout::ChunksTimeout;
+pub use rate_limit::RateLimit;
+pub use try_buffer_unordered_with_limit::TryBufferUnorderedWithLimit;
+
+/// Extension trait adding the streamx combinators to every `Stream`
+pub trait StreamxExt: Stream {
+    /// Groups items into batches of up to `capacity`, emitting early after `duration`
+    ///
+    /// The timer starts when the first item of a batch arrives, so no empty batches are emitted.
+    ///
+    /// # Arguments
+    /// * `capacity` - Maximum number of items per batch
+    /// * `duration` - Maximum time the first item of a batch waits before the batch is emitted
+    ///
+    /// # Returns
+    /// * A stream of non-empty batches
+    ///
+    /// # Panics
+    /// * If `capacity` is zero
+    fn chunks_timeout(self, capacity: usize, duration: Duration) -> ChunksTimeout<Self>
+    where
+        Self: Sized,
+    {
+        ChunksTimeout::new(self, capacity, duration)
+    }
+
+    /// Limits the stream to at most `per_second` items per second
+    ///
+    /// Items are spaced evenly and the underlying stream is not polled while waiting, so
+    /// backpressure propagates upstream.
+    ///
+    /// # Arguments
+    /// * `per_second` - Maximum number of items yielded per second
+    ///
+    /// # Returns
+    /// * A throttled stream yielding the same items
+    ///
+    /// # Panics
+    /// * If `per_second` is zero
+    fn rate_limit(self, per_second: u32) -> RateLimit<Self>
+    where
+        Self: Sized,
+    {
+        RateLimit::new(self, per_second)
+    }
+
+    /// Runs up to `limit` of the yielded futures concurrently, stopping at the first error
+    ///
+    /// Results are yielded in completion order. Once a future fails its error is yielded, all
+    /// futures still in flight are dropped and the stream ends.
+    ///
+    /// # Arguments
+    /// * `limit` - Maximum number of futures running at once
+    ///
+    /// # Returns
+    /// * A stream of the futures' results, ending after the first error
+    ///
+    /// # Panics
+    /// * If `limit` is zero
+    fn try_buffer_unordered_with_limit<T, E>(
+        self,
+        limit: usize,
+    ) -> TryBufferUnorderedWithLimit<Self>
+    where
+        Self: Sized,
+        Self::Item: Future<Output = Result<T, E>>,
+    {
+        TryBufferUnorderedWithLimit::new(self, limit)
+    }
+}
+
+impl<S: Stream + ?Sized> StreamxExt for S {}
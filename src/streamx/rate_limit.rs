@@ -0,0 +1,79 @@
+//! Throughput limiting
+//!
+//! This module provides `RateLimit`, a stream adapter that spaces items evenly so that no more
+//! than a fixed number are yielded per second. The underlying stream is only polled once the
+//! next item is allowed, so a slow consumer slows the producer instead of buffering.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures::{ready, Stream};
+use pin_project_lite::pin_project;
+use tokio::time::{Instant, Sleep};
+
+pin_project! {
+    /// Stream returned by `StreamxExt::rate_limit`
+    #[must_use = "streams do nothing unless polled"]
+    #[derive(Debug)]
+    pub struct RateLimit<S> {
+        #[pin]
+        stream: S,
+        delay: Option<Pin<Box<Sleep>>>,
+        interval: Duration,
+    }
+}
+
+impl<S: Stream> RateLimit<S> {
+    /// Creates a new RateLimit adapter
+    ///
+    /// # Arguments
+    /// * `stream` - The stream to throttle
+    /// * `per_second` - Maximum number of items yielded per second
+    ///
+    /// # Returns
+    /// * The throttled stream
+    ///
+    /// # Panics
+    /// * If `per_second` is zero
+    pub fn new(stream: S, per_second: u32) -> Self {
+        assert!(
+            per_second > 0,
+            "rate_limit per_second must be greater than zero"
+        );
+        Self {
+            stream,
+            delay: None,
+            interval: Duration::from_secs(1) / per_second,
+        }
+    }
+}
+
+impl<S: Stream> Stream for RateLimit<S> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        if let Some(delay) = this.delay.as_mut() {
+            ready!(delay.as_mut().poll(cx));
+        }
+
+        let item = ready!(this.stream.poll_next(cx));
+        if item.is_some() {
+            let next = Instant::now() + *this.interval;
+            match this.delay {
+                Some(delay) => delay.as_mut().reset(next),
+                None => *this.delay = Some(Box::pin(tokio::time::sleep_until(next))),
+            }
+        }
+        Poll::Ready(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
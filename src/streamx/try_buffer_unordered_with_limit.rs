@@ -0,0 +1,108 @@
+//! Bounded concurrent execution of fallible futures
+//!
+//! This module provides `TryBufferUnorderedWithLimit`, a stream adapter over a stream of futures
+//! returning `Result`. Up to `limit` futures run at once and their results are yielded in
+//! completion order. Unlike a plain `buffer_unordered`, the first error ends the stream: the
+//! error is yielded, the futures still in flight are dropped and no further futures are pulled
+//! from the underlying stream.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures::{stream::FuturesUnordered, Stream, StreamExt};
+use pin_project_lite::pin_project;
+
+pin_project! {
+    /// Stream returned by `StreamxExt::try_buffer_unordered_with_limit`
+    #[must_use = "streams do nothing unless polled"]
+    pub struct TryBufferUnorderedWithLimit<S: Stream> {
+        #[pin]
+        stream: S,
+        in_flight: FuturesUnordered<S::Item>,
+        limit: usize,
+        exhausted: bool,
+        failed: bool,
+    }
+}
+
+impl<S: Stream> TryBufferUnorderedWithLimit<S> {
+    /// Creates a new TryBufferUnorderedWithLimit adapter
+    ///
+    /// # Arguments
+    /// * `stream` - The stream of futures to run
+    /// * `limit` - Maximum number of futures running at once
+    ///
+    /// # Returns
+    /// * The concurrent stream of results
+    ///
+    /// # Panics
+    /// * If `limit` is zero
+    pub fn new(stream: S, limit: usize) -> Self {
+        assert!(
+            limit > 0,
+            "try_buffer_unordered_with_limit limit must be greater than zero"
+        );
+        Self {
+            stream,
+            in_flight: FuturesUnordered::new(),
+            limit,
+            exhausted: false,
+            failed: false,
+        }
+    }
+
+    /// Gets the number of futures currently running
+    ///
+    /// # Returns
+    /// * The number of in-flight futures
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+}
+
+impl<S, T, E> Stream for TryBufferUnorderedWithLimit<S>
+where
+    S: Stream,
+    S::Item: Future<Output = Result<T, E>>,
+{
+    type Item = Result<T, E>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        if *this.failed {
+            return Poll::Ready(None);
+        }
+
+        while !*this.exhausted && this.in_flight.len() < *this.limit {
+            match this.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(fut)) => this.in_flight.push(fut),
+                Poll::Ready(None) => *this.exhausted = true,
+                Poll::Pending => break,
+            }
+        }
+
+        match this.in_flight.poll_next_unpin(cx) {
+            Poll::Ready(Some(Ok(value))) => Poll::Ready(Some(Ok(value))),
+            Poll::Ready(Some(Err(err))) => {
+                *this.failed = true;
+                this.in_flight.clear();
+                Poll::Ready(Some(Err(err)))
+            }
+            Poll::Ready(None) if *this.exhausted => Poll::Ready(None),
+            Poll::Ready(None) | Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.failed {
+            return (0, Some(0));
+        }
+        let (_, upper) = self.stream.size_hint();
+        let upper = upper.and_then(|upper| upper.checked_add(self.in_flight.len()));
+        (0, upper)
+    }
+}
@@ -0,0 +1,63 @@
+//! String interpolation utilities
+//!
+//! This module provides placeholder substitution for templates such as user-facing messages.
+//! Placeholders are written as `{name}`; `{{` and `}}` produce literal braces. Placeholders
+//! without a matching value are left untouched so missing values are easy to spot.
+//!
+//! # Example
+//! ```
+//! use x::stringsx::interpolate::interpolate;
+//!
+//! let text = interpolate("Hello, {name}! You have {count} new messages.", &[
+//!     ("name", "Ada"),
+//!     ("count", "3"),
+//! ]);
+//! assert_eq!(text, "Hello, Ada! You have 3 new messages.");
+//! ```
+
+/// Replaces `{name}` placeholders in a template with the matching values
+///
+/// # Arguments
+/// * `template` - The template containing placeholders
+/// * `values` - Name/value pairs; the first pair with a matching name wins
+///
+/// # Returns
+/// * The interpolated string, with unknown placeholders kept as written
+pub fn interpolate(template: &str, values: &[(&str, &str)]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(index) = rest.find(['{', '}']) {
+        result.push_str(&rest[..index]);
+        let tail = &rest[index..];
+
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            result.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        if let Some(after) = tail.strip_prefix('}') {
+            result.push('}');
+            rest = after;
+            continue;
+        }
+
+        match tail[1..].find('}') {
+            Some(end) => {
+                let name = tail[1..=end].trim();
+                match values.iter().find(|(key, _)| *key == name) {
+                    Some((_, value)) => result.push_str(value),
+                    None => result.push_str(&tail[..end + 2]),
+                }
+                rest = &tail[end + 2..];
+            }
+            None => {
+                result.push_str(tail);
+                rest = "";
+            }
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
@@ -1,8 +1,10 @@
 //! This module provides a collection of data manipulation utilities,
-//! specifically focusing on text case transformations and data coalescing.
+//! specifically focusing on text case transformations, data coalescing and interpolation.
 //!
-//! The module exposes two main sub-modules:
+//! The module exposes three main sub-modules:
 //! - `case`: Contains functions for case manipulations (e.g. camel case, snake case)
 //! - `coalesce`: Provides data coalescing utilities
+//! - `interpolate`: Provides `{name}` placeholder substitution
 pub mod case;
 pub mod coalesce;
+pub mod interpolate;
@@ -1,6 +1,7 @@
 //! # UUID Generator Module
 //!
-//! This module provides functionality for generating UUID v4 (random) identifiers.
+//! This module provides functionality for generating UUID v4 (random) and UUID v7
+//! (time-ordered) identifiers.
 //! It wraps the uuid crate's functionality to provide a simple interface for
 //! generating new UUIDs.
 //!
@@ -30,3 +31,15 @@ use uuid::Uuid;
 pub fn generate_new_v4() -> Uuid {
     Uuid::new_v4()
 }
+
+/// Generates a new time-ordered UUID v7
+///
+/// Returns a new UUID using the v7 format (Unix timestamp followed by random bits),
+/// which sorts by creation time
+/// # Example
+/// ```
+/// let id = x::uuidx::generate_new_v7();
+/// ```
+pub fn generate_new_v7() -> Uuid {
+    Uuid::now_v7()
+}
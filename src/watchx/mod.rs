@@ -0,0 +1,353 @@
+//! # Watchx
+//!
+//! This module provides debounced file and directory watching on top of the `notify` crate.
+//! Raw filesystem notifications are coalesced over a quiet period and delivered as a single
+//! `WatchEvent` over an async channel, so a burst of writes (editors saving via temp files,
+//! deployment tools replacing a directory) results in one event instead of dozens.
+//!
+//! ## Overview
+//!
+//! The main components are:
+//! - `watch`: Watches a file or directory and returns a `Watcher` yielding debounced events
+//! - `watch_and_reload`: Parses a file and re-parses it whenever it changes
+//! - `watch_and_reload_with_debounce`: The same with a custom debounce period
+//! - `Reloadable`: Holds the latest successfully parsed value and swaps it atomically
+//!
+//! ## Usage Scenarios
+//!
+//! Ideal for:
+//! - Hot-reloading configuration files without restarting the process
+//! - Reacting to files dropped into a directory
+//!
+//! Both `watch` and `watch_and_reload` spawn a background task and must be called from within
+//! a Tokio runtime.
+//!
+//! ### Example
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use x::watchx;
+//!
+//! # async fn run() -> Result<(), x::errorsx::Errorsx> {
+//! let mut watcher = watchx::watch("./config", Duration::from_millis(200))?;
+//! while let Some(event) = watcher.recv().await {
+//!     println!("changed: {:?}", event.paths());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use notify::{
+    event::{AccessKind, AccessMode},
+    Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher as _,
+};
+use tokio::{
+    sync::{mpsc, watch},
+    task::JoinHandle,
+};
+
+use crate::errorsx::Errorsx;
+
+/// Capacity of the channel carrying debounced events to the consumer
+const EVENT_BUFFER: usize = 16;
+
+/// Quiet period used by `watch_and_reload`
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// A debounced batch of filesystem changes
+///
+/// # Fields
+/// * `paths` - Deduplicated paths that changed during the debounce window
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchEvent {
+    paths: Vec<PathBuf>,
+}
+
+impl WatchEvent {
+    /// Gets the paths that changed
+    ///
+    /// # Returns
+    /// A slice of the changed paths, in the order they were first reported
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+}
+
+/// Handle to an active filesystem watch
+///
+/// Dropping the handle stops watching and ends the background debounce task.
+///
+/// # Fields
+/// * `inner` - The underlying platform watcher, kept alive until the handle is closed or dropped
+/// * `events` - Receiver for debounced events
+#[derive(Debug)]
+pub struct Watcher {
+    inner: Option<RecommendedWatcher>,
+    events: mpsc::Receiver<WatchEvent>,
+}
+
+impl Watcher {
+    /// Waits for the next debounced change
+    ///
+    /// # Returns
+    /// The next `WatchEvent`, or `None` once the watch has stopped
+    pub async fn recv(&mut self) -> Option<WatchEvent> {
+        self.events.recv().await
+    }
+
+    /// Stops watching and closes the event channel
+    ///
+    /// The platform watcher is shut down, so no further changes are observed. Events already
+    /// buffered can still be received after calling this.
+    pub fn close(&mut self) {
+        self.inner = None;
+        self.events.close();
+    }
+}
+
+/// Watches a file or directory and delivers debounced change events
+///
+/// Directories are watched recursively. Events are emitted once no further changes have been
+/// observed for `debounce`.
+///
+/// # Parameters
+/// * `path` - The file or directory to watch
+/// * `debounce` - The quiet period that must elapse before a batch of changes is emitted
+///
+/// # Returns
+/// A `Watcher` yielding debounced events, or an `Errorsx` if the watch could not be set up
+#[track_caller]
+pub fn watch(path: impl AsRef<Path>, debounce: Duration) -> Result<Watcher, Errorsx> {
+    let path = path.as_ref();
+    let mode = if path.is_dir() {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+    spawn_watcher(path, mode, debounce, |_| true)
+}
+
+/// A value parsed from a file and kept up to date as the file changes
+///
+/// Readers always observe a complete value: a reload either replaces the value as a whole or,
+/// if parsing fails, leaves the previous value in place. Dropping the handle stops reloading.
+///
+/// # Fields
+/// * `current` - Receiver side of the channel holding the latest parsed value
+/// * `task` - Background task watching the file and re-parsing it
+#[derive(Debug)]
+pub struct Reloadable<T> {
+    current: watch::Receiver<Arc<T>>,
+    task: JoinHandle<()>,
+}
+
+impl<T> Reloadable<T> {
+    /// Gets the latest successfully parsed value
+    ///
+    /// # Returns
+    /// A shared reference to the current value
+    pub fn current(&self) -> Arc<T> {
+        self.current.borrow().clone()
+    }
+
+    /// Subscribes to value changes
+    ///
+    /// # Returns
+    /// A receiver that is notified each time a reload succeeds
+    pub fn subscribe(&self) -> watch::Receiver<Arc<T>> {
+        self.current.clone()
+    }
+}
+
+impl<T> Drop for Reloadable<T> {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Parses a file and re-parses it whenever it changes
+///
+/// Changes are debounced with `DEFAULT_DEBOUNCE`; use `watch_and_reload_with_debounce` to pick
+/// the quiet period.
+///
+/// # Parameters
+/// * `path` - The file to parse and watch
+/// * `parse_fn` - Function that parses the file at the given path
+///
+/// # Returns
+/// A `Reloadable` holding the initially parsed value, or an `Errorsx` if the initial parse or
+/// the watch setup failed
+#[track_caller]
+pub fn watch_and_reload<T, F>(path: impl AsRef<Path>, parse_fn: F) -> Result<Reloadable<T>, Errorsx>
+where
+    T: Send + Sync + 'static,
+    F: Fn(&Path) -> Result<T, Errorsx> + Send + Sync + 'static,
+{
+    watch_and_reload_with_debounce(path, DEFAULT_DEBOUNCE, parse_fn)
+}
+
+/// Parses a file and re-parses it whenever it changes, with a custom debounce period
+///
+/// The parent directory is watched rather than the file itself so that editors and tools that
+/// replace the file through a rename are picked up. Reloads run `parse_fn` on Tokio's blocking
+/// thread pool. A failed reload is logged and the previous value is kept.
+///
+/// # Parameters
+/// * `path` - The file to parse and watch
+/// * `debounce` - The quiet period that must elapse before the file is re-parsed
+/// * `parse_fn` - Function that parses the file at the given path
+///
+/// # Returns
+/// A `Reloadable` holding the initially parsed value, or an `Errorsx` if the initial parse or
+/// the watch setup failed
+#[track_caller]
+pub fn watch_and_reload_with_debounce<T, F>(
+    path: impl AsRef<Path>,
+    debounce: Duration,
+    parse_fn: F,
+) -> Result<Reloadable<T>, Errorsx>
+where
+    T: Send + Sync + 'static,
+    F: Fn(&Path) -> Result<T, Errorsx> + Send + Sync + 'static,
+{
+    let path = path.as_ref().to_path_buf();
+    let initial = parse_fn(&path)?;
+    let parse_fn = Arc::new(parse_fn);
+
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_os_string())
+        .ok_or_else(|| {
+            Errorsx::builder("Failed to watch file")
+                .with_context(format!("Path has no file name: {}", path.display()))
+                .build()
+        })?;
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+
+    let mut watcher = spawn_watcher(
+        &parent,
+        RecursiveMode::NonRecursive,
+        debounce,
+        move |changed| changed.file_name() == Some(file_name.as_os_str()),
+    )?;
+
+    let (tx, rx) = watch::channel(Arc::new(initial));
+    let task = tokio::spawn(async move {
+        while watcher.recv().await.is_some() {
+            let parse_fn = Arc::clone(&parse_fn);
+            let parse_path = path.clone();
+            match tokio::task::spawn_blocking(move || parse_fn(&parse_path)).await {
+                Ok(Ok(value)) => {
+                    tx.send_replace(Arc::new(value));
+                    tracing::debug!(path = %path.display(), "reloaded watched file");
+                }
+                Ok(Err(err)) => {
+                    tracing::warn!(
+                        path = %path.display(),
+                        error = err.message(),
+                        "failed to reload watched file, keeping previous value"
+                    );
+                }
+                Err(err) => {
+                    tracing::warn!(
+                        path = %path.display(),
+                        error = %err,
+                        "reload of watched file panicked, keeping previous value"
+                    );
+                }
+            }
+        }
+    });
+
+    Ok(Reloadable { current: rx, task })
+}
+
+/// Sets up the platform watcher and the debounce task feeding the returned `Watcher`
+#[track_caller]
+fn spawn_watcher<P>(
+    path: &Path,
+    mode: RecursiveMode,
+    debounce: Duration,
+    filter: P,
+) -> Result<Watcher, Errorsx>
+where
+    P: Fn(&Path) -> bool + Send + 'static,
+{
+    let (raw_tx, raw_rx) = mpsc::unbounded_channel::<Vec<PathBuf>>();
+    let mut inner = notify::recommended_watcher(move |res: notify::Result<Event>| match res {
+        Ok(event) if is_change(&event.kind) => {
+            let paths: Vec<PathBuf> = event.paths.into_iter().filter(|p| filter(p)).collect();
+            if !paths.is_empty() {
+                let _ = raw_tx.send(paths);
+            }
+        }
+        Ok(_) => {}
+        Err(err) => tracing::warn!(error = %err, "filesystem watch error"),
+    })
+    .map_err(|err| {
+        Errorsx::builder("Failed to create filesystem watcher")
+            .with_source(err)
+            .build()
+    })?;
+
+    inner.watch(path, mode).map_err(|err| {
+        Errorsx::builder("Failed to watch path")
+            .with_context(format!("Watching: {}", path.display()))
+            .with_source(err)
+            .build()
+    })?;
+
+    let (tx, events) = mpsc::channel(EVENT_BUFFER);
+    tokio::spawn(debounce_events(raw_rx, tx, debounce));
+
+    Ok(Watcher {
+        inner: Some(inner),
+        events,
+    })
+}
+
+/// Coalesces raw notifications until `window` passes without a new one, then emits a batch
+async fn debounce_events(
+    mut raw: mpsc::UnboundedReceiver<Vec<PathBuf>>,
+    tx: mpsc::Sender<WatchEvent>,
+    window: Duration,
+) {
+    while let Some(first) = raw.recv().await {
+        let mut paths = first;
+        let mut open = true;
+        while open {
+            match tokio::time::timeout(window, raw.recv()).await {
+                Ok(Some(more)) => paths.extend(more),
+                Ok(None) => open = false,
+                Err(_) => break,
+            }
+        }
+
+        let mut unique = Vec::with_capacity(paths.len());
+        for path in paths {
+            if !unique.contains(&path) {
+                unique.push(path);
+            }
+        }
+        if tx.send(WatchEvent { paths: unique }).await.is_err() || !open {
+            return;
+        }
+    }
+}
+
+/// Returns whether an event kind represents an actual change rather than a read
+fn is_change(kind: &EventKind) -> bool {
+    match kind {
+        EventKind::Access(AccessKind::Close(AccessMode::Write)) => true,
+        EventKind::Access(_) => false,
+        _ => true,
+    }
+}